@@ -0,0 +1,64 @@
+//! Compares passing the size hint to `serialize_seq` against passing `None`.
+//!
+//! Run with `cargo bench --bench size_hint`.
+
+use std::io;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde::Serialize;
+use serde_iter::seq::options::SeqHint;
+
+#[derive(Clone)]
+struct Chain<'a>(&'a [u32]);
+
+impl<'a> IntoIterator for Chain<'a> {
+    type Item = u32;
+    type IntoIter = Box<dyn Iterator<Item = u32> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(
+            self.0
+                .iter()
+                .copied()
+                .map(|value| value * 2)
+                .chain(self.0.iter().copied().skip(10).step_by(7))
+                .zip(self.0.iter().rev())
+                .map(|(left, right)| left ^ right),
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct LowerHint<'a> {
+    #[serde(with = "serde_iter::seq")]
+    bar: Chain<'a>,
+}
+
+struct Hinted<'a>(Chain<'a>, SeqHint);
+
+impl<'a> Serialize for Hinted<'a> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_iter::seq::serialize_hint(&self.0, self.1, serializer)
+    }
+}
+
+fn size_hint(c: &mut Criterion) {
+    let data: Vec<u32> = (0..100_000).collect();
+    let mut group = c.benchmark_group("size_hint");
+
+    let lower_hint = LowerHint { bar: Chain(&data) };
+    group.bench_function("seq::serialize", |b| {
+        b.iter(|| serde_json::to_writer(io::sink(), black_box(&lower_hint)))
+    });
+    for (name, hint) in [("lower", SeqHint::Lower), ("none", SeqHint::None)] {
+        let hinted = Hinted(Chain(&data), hint);
+        group.bench_function(name, |b| {
+            b.iter(|| serde_json::to_writer(io::sink(), black_box(&hinted)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, size_hint);
+criterion_main!(benches);
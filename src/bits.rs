@@ -0,0 +1,227 @@
+//! Serializes an iterator of `bool` into a compact bitset string.
+//!
+//! Bits are packed into bytes (the first bit being the most significant bit of the first byte),
+//! and emitted as a struct with two fields:
+//! `len`, the number of bits, and `data`, the packed bytes as a lowercase hex string.
+//! This is far more compact than `[true, false, ...]` for dense boolean arrays.
+//!
+//! The `deserialize` function unpacks the bits back into any `FromIterator<bool>` container.
+//!
+//! *This module requires the "bits" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::bits")]
+//!     bar: Vec<bool>,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: vec![true, false, true, true],
+//! };
+//! let value = serde_json::to_value(&foo).unwrap();
+//! assert_eq!(value, serde_json::json!({
+//!     "bar": {"len": 4, "data": "b0"}
+//! }));
+//!
+//! let foo: Foo = serde_json::from_value(value).unwrap();
+//! assert_eq!(foo.bar, vec![true, false, true, true]);
+//! ```
+
+use std::convert::TryFrom;
+use std::fmt::{self, Write};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{SerializeStruct, Serializer};
+
+const FIELDS: &[&str] = &["len", "data"];
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = bool> + Clone,
+{
+    let iter = iter.clone().into_iter();
+    let mut len = 0_usize;
+    let mut bytes = Vec::with_capacity(iter.size_hint().0.div_ceil(8));
+    for bit in iter {
+        if len.is_multiple_of(8) {
+            bytes.push(0_u8);
+        }
+        if bit {
+            if let Some(byte) = bytes.last_mut() {
+                *byte |= 0x80 >> (len % 8);
+            }
+        }
+        len += 1;
+    }
+
+    let mut data = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        write!(data, "{:02x}", byte).map_err(serde::ser::Error::custom)?;
+    }
+
+    let mut st = serializer.serialize_struct("Bits", 2)?;
+    st.serialize_field("len", &len)?;
+    st.serialize_field("data", &data)?;
+    st.end()
+}
+
+/// Refer to the [module-level documentation](index.html).
+pub fn deserialize<'de, D, C>(deserializer: D) -> Result<C, D::Error>
+where
+    D: Deserializer<'de>,
+    C: FromIterator<bool>,
+{
+    deserializer.deserialize_struct("Bits", FIELDS, BitsVisitor(PhantomData))
+}
+
+struct BitsVisitor<C>(PhantomData<C>);
+
+impl<'de, C> Visitor<'de> for BitsVisitor<C>
+where
+    C: FromIterator<bool>,
+{
+    type Value = C;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a bitset with a length and a hex string")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<C, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let len = seq
+            .next_element::<usize>()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let data = seq
+            .next_element::<String>()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        unpack(len, &data)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<C, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut len = None;
+        let mut data = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "len" => {
+                    if len.is_some() {
+                        return Err(de::Error::duplicate_field("len"));
+                    }
+                    len = Some(map.next_value::<usize>()?);
+                }
+                "data" => {
+                    if data.is_some() {
+                        return Err(de::Error::duplicate_field("data"));
+                    }
+                    data = Some(map.next_value::<String>()?);
+                }
+                other => return Err(de::Error::unknown_field(other, FIELDS)),
+            }
+        }
+        let len = len.ok_or_else(|| de::Error::missing_field("len"))?;
+        let data = data.ok_or_else(|| de::Error::missing_field("data"))?;
+        unpack(len, &data)
+    }
+}
+
+fn unpack<C, E>(len: usize, data: &str) -> Result<C, E>
+where
+    C: FromIterator<bool>,
+    E: de::Error,
+{
+    if data.len() != len.div_ceil(8) * 2 {
+        return Err(E::invalid_length(
+            data.len(),
+            &"a hex string of the number of bytes required by len",
+        ));
+    }
+
+    let mut bytes = Vec::with_capacity(data.len() / 2);
+    let digits = data.as_bytes();
+    for pair in digits.chunks(2) {
+        let mut byte = 0_u8;
+        for &digit in pair {
+            let nibble = char::from(digit)
+                .to_digit(16)
+                .ok_or_else(|| E::invalid_value(de::Unexpected::Str(data), &"a hex string"))?;
+            byte = (byte << 4) | u8::try_from(nibble).map_err(E::custom)?;
+        }
+        bytes.push(byte);
+    }
+
+    Ok((0..len)
+        .map(|index| {
+            bytes
+                .get(index / 8)
+                .is_some_and(|byte| byte & (0x80 >> (index % 8)) != 0)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::{from_value, json, to_value};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Foo {
+        #[serde(with = "super")]
+        bar: Vec<bool>,
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo { bar: vec![] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"len": 0, "data": ""}
+            })
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let bits = vec![
+            true, false, true, true, false, false, true, false, true, true,
+        ];
+        let value = to_value(Foo { bar: bits.clone() });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"len": 10, "data": "b2c0"}
+            })
+        );
+
+        let foo: Foo = from_value(value).expect("Failed to deserialize");
+        assert_eq!(foo, Foo { bar: bits });
+    }
+
+    #[test]
+    fn test_length_mismatch() {
+        let result = from_value::<Foo>(json!({
+            "bar": {"len": 10, "data": "b2"}
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_invalid_hex() {
+        let result = from_value::<Foo>(json!({
+            "bar": {"len": 4, "data": "zz"}
+        }));
+        assert!(result.is_err());
+    }
+}
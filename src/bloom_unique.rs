@@ -0,0 +1,233 @@
+//! Serializes an iterator of serializables into a serde sequence,
+//! approximately removing duplicates with a bloom filter.
+//!
+//! Unlike collecting seen items into a `HashSet`,
+//! a bloom filter uses a fixed amount of memory regardless of the size of the items,
+//! which makes it suitable for best-effort deduplication of very large streams.
+//!
+//! # False positives
+//! A bloom filter may report an item as seen even though it was not,
+//! so **some unique items may be dropped** from the output.
+//! Duplicates are never emitted twice.
+//!
+//! The filter is sized from the upper bound of the iterator size hint,
+//! or from the lower bound if there is no upper bound,
+//! clamped between 1024 and 2<sup>24</sup> items.
+//! It uses 10 bits per item, which gives a false positive rate of about 1%
+//! as long as the number of unique items does not exceed the expected number.
+//!
+//! Beyond that, the false positive rate grows quickly:
+//! with twice the expected number of unique items it is already about 10%,
+//! and with ten times as many, most unique items are silently dropped.
+//! This happens for streams of unknown length, whose lower bound is usually 0,
+//! so the filter only fits 1024 items.
+//! Use [`serialize_sized`](fn.serialize_sized.html) with the expected number of items
+//! for such streams, or to configure the number of bits per item.
+//!
+//! *This module requires the "bloom" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::bloom_unique")]
+//!     bar: Vec<i32>,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: vec![1, 2, 1, 3, 2],
+//! };
+//! assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+//!     "bar": [1, 2, 3]
+//! }));
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::convert::TryFrom;
+use std::hash::{Hash, Hasher};
+
+use serde::ser::{Error, Serialize, SerializeSeq, Serializer};
+
+const MIN_EXPECTED_ITEMS: usize = 1024;
+const MAX_EXPECTED_ITEMS: usize = 1 << 24;
+const DEFAULT_BITS_PER_ITEM: usize = 10;
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize + Hash,
+{
+    let (lower, upper) = iter.clone().into_iter().size_hint();
+    let expected = upper.unwrap_or(lower);
+    serialize_sized(
+        iter,
+        expected.clamp(MIN_EXPECTED_ITEMS, MAX_EXPECTED_ITEMS),
+        DEFAULT_BITS_PER_ITEM,
+        serializer,
+    )
+}
+
+/// Deduplicates with a bloom filter sized for `expected_items` unique items,
+/// using `bits_per_item` bits for each.
+///
+/// The false positive rate is about `0.6185.powi(bits_per_item)`,
+/// e.g. about 1% for 10 bits per item and about 0.1% for 15 bits per item.
+///
+/// Returns an error if `bits_per_item` is zero.
+pub fn serialize_sized<S, T, V>(
+    iter: &T,
+    expected_items: usize,
+    bits_per_item: usize,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize + Hash,
+{
+    if bits_per_item == 0 {
+        return Err(S::Error::custom(
+            "bloom filter bits per item must be nonzero",
+        ));
+    }
+
+    let mut filter = Bloom::new(
+        expected_items.max(1).saturating_mul(bits_per_item),
+        bits_per_item,
+    );
+    let mut seq = serializer.serialize_seq(None)?;
+    for value in iter.clone() {
+        if filter.insert(&value) {
+            seq.serialize_element(&value)?;
+        }
+    }
+    seq.end()
+}
+
+struct Bloom {
+    words: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u64,
+}
+
+impl Bloom {
+    fn new(num_bits: usize, bits_per_item: usize) -> Self {
+        let words = vec![0; num_bits.div_ceil(64)];
+        // approximates the optimal number of hash functions, `bits_per_item * ln 2`
+        let num_hashes = (bits_per_item.saturating_mul(693) / 1000).max(1);
+        Self {
+            num_bits: u64::try_from(words.len()).unwrap_or(u64::MAX / 64) * 64,
+            words,
+            num_hashes: u64::try_from(num_hashes).unwrap_or(u64::MAX),
+        }
+    }
+
+    /// Inserts the item, returning `true` if it was definitely not inserted before.
+    fn insert<V: Hash>(&mut self, value: &V) -> bool {
+        let h1 = hash_with_seed(value, 0);
+        let h2 = hash_with_seed(value, 1) | 1;
+
+        let mut new = false;
+        for i in 0..self.num_hashes {
+            let bit = h1.wrapping_add(i.wrapping_mul(h2)) % self.num_bits;
+            let word = usize::try_from(bit / 64)
+                .ok()
+                .and_then(|index| self.words.get_mut(index));
+            if let Some(word) = word {
+                let mask = 1 << (bit % 64);
+                if *word & mask == 0 {
+                    *word |= mask;
+                    new = true;
+                }
+            }
+        }
+        new
+    }
+}
+
+fn hash_with_seed<V: Hash>(value: &V, seed: u8) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    seed.hash(&mut hasher);
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_value};
+
+    #[derive(Serialize)]
+    struct Foo<T>
+    where
+        T: IntoIterator<Item = &'static str> + Clone,
+    {
+        #[serde(with = "super")]
+        bar: T,
+    }
+
+    fn zero_bits<S: Serializer>(iter: &[i32; 2], serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize_sized(iter, 16, 0, serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Zero {
+        #[serde(serialize_with = "zero_bits")]
+        bar: [i32; 2],
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo { bar: vec![] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": []
+            })
+        );
+    }
+
+    #[test]
+    fn test_dedup() {
+        let value = to_value(Foo {
+            bar: vec!["a", "b", "a", "c", "b", "a"],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": ["a", "b", "c"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_sized_from_upper_bound() {
+        #[derive(Serialize)]
+        struct Numbers<T>
+        where
+            T: IntoIterator<Item = u32> + Clone,
+        {
+            #[serde(with = "super")]
+            bar: T,
+        }
+
+        // `Filter` has a lower bound of 0, which would only fit 1024 items.
+        let evens = (0..40_000).filter(|value| value % 2 == 0);
+        let value = to_value(Numbers { bar: evens });
+        let value = value.expect("Failed to serialize");
+        let emitted = value
+            .get("bar")
+            .and_then(|bar| bar.as_array())
+            .map(Vec::len);
+        assert!(emitted.is_some_and(|emitted| emitted > 19_000));
+    }
+
+    #[test]
+    fn test_zero_bits() {
+        assert!(to_value(Zero { bar: [1, 2] }).is_err());
+    }
+}
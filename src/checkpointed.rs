@@ -0,0 +1,184 @@
+//! Serializes an iterator of serializables into a serde sequence,
+//! interleaving a checkpoint marker every N elements.
+//!
+//! This is useful for resumable streams,
+//! where consumers can track progress by the markers they have seen.
+//!
+//! A marker is emitted right after every `interval`-th element,
+//! and its content is produced by the `marker` function from the number of elements emitted so
+//! far. [`Checkpoint`](struct.Checkpoint.html) is provided as a default marker,
+//! which serializes as `{"_checkpoint": count}`.
+//!
+//! *This module requires the "checkpointed" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! fn checkpoints<S: serde::Serializer>(
+//!     iter: &std::ops::Range<i32>,
+//!     serializer: S,
+//! ) -> Result<S::Ok, S::Error> {
+//!     serde_iter::checkpointed::serialize(iter, 2, serde_iter::checkpointed::Checkpoint, serializer)
+//! }
+//!
+//! #[derive(serde::Serialize)]
+//! struct Foo {
+//!     #[serde(serialize_with = "checkpoints")]
+//!     bar: std::ops::Range<i32>,
+//! }
+//!
+//! let foo = Foo { bar: 0..3 };
+//! assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+//!     "bar": [0, 1, {"_checkpoint": 2}, 2]
+//! }));
+//! ```
+
+use serde::ser::{Error, Serialize, SerializeMap, SerializeSeq, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+///
+/// Returns an error if `interval` is zero.
+pub fn serialize<S, T, V, F, M>(
+    iter: &T,
+    interval: usize,
+    marker: F,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+    F: Fn(usize) -> M,
+    M: Serialize,
+{
+    if interval == 0 {
+        return Err(S::Error::custom("checkpoint interval must be nonzero"));
+    }
+
+    let iter = iter.clone().into_iter();
+    let hint = iter.size_hint().0;
+    let mut seq = serializer.serialize_seq(Some(hint.saturating_add(hint / interval)))?;
+    for (index, value) in iter.enumerate() {
+        seq.serialize_element(&value)?;
+        let count = index + 1;
+        if count % interval == 0 {
+            seq.serialize_element(&marker(count))?;
+        }
+    }
+    seq.end()
+}
+
+/// The default checkpoint marker, serialized as `{"_checkpoint": count}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint(pub usize);
+
+impl Serialize for Checkpoint {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry("_checkpoint", &self.0)?;
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_string, to_value};
+
+    use super::Checkpoint;
+
+    fn every_four<S: Serializer>(iter: &Range<i32>, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, 4, Checkpoint, serializer)
+    }
+
+    fn every_zero<S: Serializer>(iter: &Range<i32>, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, 0, Checkpoint, serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(serialize_with = "every_four")]
+        bar: Range<i32>,
+    }
+
+    #[derive(Serialize)]
+    struct Zero {
+        #[serde(serialize_with = "every_zero")]
+        bar: Range<i32>,
+    }
+
+    #[test]
+    fn test_markers() {
+        let value = to_value(Foo { bar: 0..10 });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": [
+                    0, 1, 2, 3, {"_checkpoint": 4},
+                    4, 5, 6, 7, {"_checkpoint": 8},
+                    8, 9
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo { bar: 0..0 });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": []
+            })
+        );
+    }
+
+    #[test]
+    fn test_zero_interval() {
+        let err = to_value(Zero { bar: 0..10 }).expect_err("Zero interval should fail");
+        assert!(err.to_string().contains("nonzero"));
+    }
+
+    #[test]
+    fn test_huge_size_hint() {
+        /// An iterator that overstates its lower bound.
+        #[derive(Clone)]
+        struct Liar(Range<i32>);
+
+        impl Iterator for Liar {
+            type Item = i32;
+
+            fn next(&mut self) -> Option<i32> {
+                self.0.next()
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (usize::MAX, None)
+            }
+        }
+
+        fn every_one<S: Serializer>(iter: &Liar, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize(iter, 1, Checkpoint, serializer)
+        }
+
+        #[derive(Serialize)]
+        struct Lying {
+            #[serde(serialize_with = "every_one")]
+            bar: Liar,
+        }
+
+        // `serde_json::Value` preallocates from the hint, so write a string instead.
+        let output = to_string(&Lying { bar: Liar(0..2) });
+        let output = output.expect("Failed to serialize");
+        assert_eq!(
+            output,
+            r#"{"bar":[0,{"_checkpoint":1},1,{"_checkpoint":2}]}"#
+        );
+    }
+}
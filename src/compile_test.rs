@@ -0,0 +1,42 @@
+//! Instantiates the core serializers in `no_std` builds.
+//!
+//! This module is only compiled when the "std" feature is disabled,
+//! so that building with `--no-default-features --features seq,map,once`
+//! fails if any of them accidentally depends on the standard library.
+//! Nothing here is meant to be called.
+
+#![allow(dead_code)]
+
+use core::ops::Range;
+
+use serde::ser::{Serialize, Serializer};
+
+#[cfg(feature = "seq")]
+struct Seq(Range<u32>);
+
+#[cfg(feature = "seq")]
+impl Serialize for Seq {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::seq::serialize(&self.0, serializer)
+    }
+}
+
+#[cfg(feature = "map")]
+struct Map(Range<u32>);
+
+#[cfg(feature = "map")]
+impl Serialize for Map {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::map::serialize(&self.0.clone().map(|key| (key, key)), serializer)
+    }
+}
+
+#[cfg(all(feature = "seq", feature = "once"))]
+struct Once(crate::CloneOnce<u32, Range<u32>>);
+
+#[cfg(all(feature = "seq", feature = "once"))]
+impl Serialize for Once {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::seq::serialize(&self.0, serializer)
+    }
+}
@@ -0,0 +1,104 @@
+//! Serializes an iterator of serializables into a serde map keyed by content hash.
+//!
+//! Each element is encoded as JSON and hashed with SHA-256,
+//! and the output is a map from the lowercase hex digest to the element,
+//! i.e. `{hash: element, ...}`.
+//! This is useful for content-addressable stores.
+//!
+//! Elements with identical content have the same hash,
+//! so only the first of them is emitted.
+//!
+//! *This module requires the "content_map" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::content_map")]
+//!     bar: Vec<&'static str>,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: vec!["qux", "qux"],
+//! };
+//! assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+//!     "bar": {
+//!         "74a10314c221f3fdaf1cf9ef56e562dd1a02f4abfe9cea68c1f4a974bb3ef13f": "qux"
+//!     }
+//! }));
+//! ```
+
+use std::collections::HashSet;
+
+use serde::ser::{Error, Serialize, SerializeMap, Serializer};
+
+use crate::digest;
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut map = serializer.serialize_map(None)?;
+    let mut seen = HashSet::new();
+    for value in iter {
+        let hash = digest::content_digest(b"", &value).map_err(S::Error::custom)?;
+        if seen.insert(hash) {
+            map.serialize_entry(&digest::to_hex(&hash), &value)?;
+        }
+    }
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde_json::{json, to_value, Value};
+
+    #[derive(Serialize)]
+    struct Foo<T>
+    where
+        T: IntoIterator<Item = i32> + Clone,
+    {
+        #[serde(with = "super")]
+        bar: T,
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo { bar: vec![] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {}
+            })
+        );
+    }
+
+    #[test]
+    fn test_duplicates_collapse() {
+        let value = to_value(Foo { bar: vec![3, 3] });
+        let value = value.expect("Failed to serialize");
+        let map = match value.get("bar") {
+            Some(Value::Object(map)) => map,
+            _ => panic!("Expected an object"),
+        };
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.values().next(), Some(&json!(3)));
+    }
+
+    #[test]
+    fn test_distinct() {
+        let value = to_value(Foo { bar: vec![1, 2, 1] });
+        let value = value.expect("Failed to serialize");
+        let map = match value.get("bar") {
+            Some(Value::Object(map)) => map,
+            _ => panic!("Expected an object"),
+        };
+        assert_eq!(map.len(), 2);
+    }
+}
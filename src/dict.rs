@@ -0,0 +1,211 @@
+//! Serializes an iterator of serializables into a dictionary of distinct elements
+//! and a sequence of references into it.
+//!
+//! The output is `{"dict": [...], "refs": [...]}`,
+//! where `dict` contains each distinct element once, in the order of its first appearance,
+//! and `refs` contains the index into `dict` of each element of the iterator.
+//! This dramatically shrinks sequences with many repeated, large values.
+//!
+//! The `deserialize` function reconstructs the original sequence
+//! into any `FromIterator` container.
+//!
+//! *This module requires the "dict" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::dict")]
+//!     bar: Vec<String>,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: vec!["x".to_owned(), "y".to_owned(), "x".to_owned()],
+//! };
+//! let value = serde_json::to_value(&foo).unwrap();
+//! assert_eq!(value, serde_json::json!({
+//!     "bar": {"dict": ["x", "y"], "refs": [0, 1, 0]}
+//! }));
+//!
+//! let foo: Foo = serde_json::from_value(value).unwrap();
+//! assert_eq!(foo.bar, vec!["x", "y", "x"]);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+const FIELDS: &[&str] = &["dict", "refs"];
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize + Eq + Hash + Clone,
+{
+    let mut indices = HashMap::new();
+    let mut dict = Vec::new();
+    let mut refs = Vec::new();
+    for value in iter.clone() {
+        let index = *indices.entry(value.clone()).or_insert_with(|| {
+            dict.push(value);
+            dict.len() - 1
+        });
+        refs.push(index);
+    }
+
+    let mut st = serializer.serialize_struct("Dict", 2)?;
+    st.serialize_field("dict", &dict)?;
+    st.serialize_field("refs", &refs)?;
+    st.end()
+}
+
+/// Refer to the [module-level documentation](index.html).
+///
+/// Returns an error if a reference is out of bounds for the dictionary.
+pub fn deserialize<'de, D, C, V>(deserializer: D) -> Result<C, D::Error>
+where
+    D: Deserializer<'de>,
+    C: FromIterator<V>,
+    V: Deserialize<'de> + Clone,
+{
+    deserializer.deserialize_struct("Dict", FIELDS, DictVisitor(PhantomData))
+}
+
+struct DictVisitor<C, V>(PhantomData<(C, V)>);
+
+impl<'de, C, V> Visitor<'de> for DictVisitor<C, V>
+where
+    C: FromIterator<V>,
+    V: Deserialize<'de> + Clone,
+{
+    type Value = C;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a dictionary of values and a sequence of references")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<C, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let dict = seq
+            .next_element::<Vec<V>>()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let refs = seq
+            .next_element::<Vec<usize>>()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        resolve(&dict, &refs)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<C, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut dict = None;
+        let mut refs = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "dict" => {
+                    if dict.is_some() {
+                        return Err(de::Error::duplicate_field("dict"));
+                    }
+                    dict = Some(map.next_value::<Vec<V>>()?);
+                }
+                "refs" => {
+                    if refs.is_some() {
+                        return Err(de::Error::duplicate_field("refs"));
+                    }
+                    refs = Some(map.next_value::<Vec<usize>>()?);
+                }
+                other => return Err(de::Error::unknown_field(other, FIELDS)),
+            }
+        }
+        let dict = dict.ok_or_else(|| de::Error::missing_field("dict"))?;
+        let refs = refs.ok_or_else(|| de::Error::missing_field("refs"))?;
+        resolve(&dict, &refs)
+    }
+}
+
+fn resolve<C, V, E>(dict: &[V], refs: &[usize]) -> Result<C, E>
+where
+    C: FromIterator<V>,
+    V: Clone,
+    E: de::Error,
+{
+    refs.iter()
+        .map(|&index| {
+            dict.get(index).cloned().ok_or_else(|| {
+                E::custom(format_args!(
+                    "reference {} is out of bounds for a dictionary of {} values",
+                    index,
+                    dict.len()
+                ))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::{from_value, json, to_value};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Foo {
+        #[serde(with = "super")]
+        bar: Vec<String>,
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|&value| value.to_owned()).collect()
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo { bar: vec![] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"dict": [], "refs": []}
+            })
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let value = to_value(Foo {
+            bar: strings(&["x", "y", "x", "x"]),
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"dict": ["x", "y"], "refs": [0, 1, 0, 0]}
+            })
+        );
+
+        let foo: Foo = from_value(value).expect("Failed to deserialize");
+        assert_eq!(
+            foo,
+            Foo {
+                bar: strings(&["x", "y", "x", "x"])
+            }
+        );
+    }
+
+    #[test]
+    fn test_out_of_bounds() {
+        let result = from_value::<Foo>(json!({
+            "bar": {"dict": ["x"], "refs": [0, 1]}
+        }));
+        assert!(result.is_err());
+    }
+}
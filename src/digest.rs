@@ -0,0 +1,30 @@
+//! Stable content hashing shared by the hash-based serializers.
+
+use std::fmt::Write;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// Computes the SHA-256 digest of the JSON encoding of `value`,
+/// preceded by `prefix` for domain separation.
+pub(crate) fn content_digest<V: Serialize>(
+    prefix: &[u8],
+    value: &V,
+) -> Result<[u8; 32], serde_json::Error> {
+    let bytes = serde_json::to_vec(value)?;
+    Ok(Sha256::new()
+        .chain_update(prefix)
+        .chain_update(&bytes)
+        .finalize()
+        .into())
+}
+
+/// Encodes the digest as a lowercase hex string.
+pub(crate) fn to_hex(digest: &[u8]) -> String {
+    let mut hex = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        // writing to a `String` never fails
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    hex
+}
@@ -0,0 +1,202 @@
+//! Serializes an iterator of `i64` with delta-of-delta encoding.
+//!
+//! This encoding (as used by Gorilla-style time series databases) is compact for monotonic
+//! series with regular intervals, such as timestamps.
+//! The output sequence contains the first value, then the delta between the first two values,
+//! then the change in delta for each subsequent value,
+//! so `[1000, 1010, 1020, 1031]` is encoded as `[1000, 10, 0, 1]`.
+//!
+//! The `deserialize` function decodes the sequence back into any `FromIterator<i64>` container.
+//! Both directions use checked arithmetic, failing on overflow instead of wrapping.
+//!
+//! *This module requires the "dod" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::dod")]
+//!     bar: Vec<i64>,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: vec![1000, 1010, 1020, 1031],
+//! };
+//! let value = serde_json::to_value(&foo).unwrap();
+//! assert_eq!(value, serde_json::json!({
+//!     "bar": [1000, 10, 0, 1]
+//! }));
+//!
+//! let foo: Foo = serde_json::from_value(value).unwrap();
+//! assert_eq!(foo.bar, vec![1000, 1010, 1020, 1031]);
+//! ```
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::{self, SerializeSeq, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+///
+/// Returns an error if a delta or delta-of-delta overflows `i64`.
+pub fn serialize<S, T>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = i64> + Clone,
+{
+    let iter = iter.clone().into_iter();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    let mut prev_value: Option<i64> = None;
+    let mut prev_delta = None;
+    for value in iter {
+        let encoded = match prev_value {
+            None => value,
+            Some(prev_value) => {
+                let delta = value
+                    .checked_sub(prev_value)
+                    .ok_or_else(overflow::<S::Error>)?;
+                let encoded = match prev_delta {
+                    None => delta,
+                    Some(prev_delta) => delta
+                        .checked_sub(prev_delta)
+                        .ok_or_else(overflow::<S::Error>)?,
+                };
+                prev_delta = Some(delta);
+                encoded
+            }
+        };
+        prev_value = Some(value);
+        seq.serialize_element(&encoded)?;
+    }
+    seq.end()
+}
+
+const OVERFLOW: &str = "delta-of-delta encoding overflows i64";
+
+fn overflow<E: ser::Error>() -> E {
+    E::custom(OVERFLOW)
+}
+
+fn overflow_de<E: de::Error>() -> E {
+    E::custom(OVERFLOW)
+}
+
+/// Refer to the [module-level documentation](index.html).
+///
+/// Returns an error if a decoded value overflows `i64`.
+pub fn deserialize<'de, D, C>(deserializer: D) -> Result<C, D::Error>
+where
+    D: Deserializer<'de>,
+    C: FromIterator<i64>,
+{
+    deserializer.deserialize_seq(DodVisitor(PhantomData))
+}
+
+struct DodVisitor<C>(PhantomData<C>);
+
+impl<'de, C> Visitor<'de> for DodVisitor<C>
+where
+    C: FromIterator<i64>,
+{
+    type Value = C;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a delta-of-delta encoded sequence of integers")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<C, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut values = Vec::with_capacity(crate::size_hint::cautious::<i64>(seq.size_hint()));
+        let mut prev_value: Option<i64> = None;
+        let mut prev_delta = None;
+        while let Some(encoded) = seq.next_element::<i64>()? {
+            let value = match prev_value {
+                None => encoded,
+                Some(prev_value) => {
+                    let delta = match prev_delta {
+                        None => encoded,
+                        Some(prev_delta) => encoded
+                            .checked_add(prev_delta)
+                            .ok_or_else(overflow_de::<A::Error>)?,
+                    };
+                    prev_delta = Some(delta);
+                    prev_value
+                        .checked_add(delta)
+                        .ok_or_else(overflow_de::<A::Error>)?
+                }
+            };
+            prev_value = Some(value);
+            values.push(value);
+        }
+        Ok(values.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::{from_value, json, to_value};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Foo {
+        #[serde(with = "super")]
+        bar: Vec<i64>,
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo { bar: vec![] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": []
+            })
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let timestamps: Vec<i64> = (0..6).map(|i| 1_600_000_000 + i * 60).collect();
+        let value = to_value(Foo {
+            bar: timestamps.clone(),
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": [1_600_000_000, 60, 0, 0, 0, 0]
+            })
+        );
+
+        let foo: Foo = from_value(value).expect("Failed to deserialize");
+        assert_eq!(foo, Foo { bar: timestamps });
+    }
+
+    #[test]
+    fn test_serialize_overflow() {
+        assert!(to_value(Foo {
+            bar: vec![i64::MIN, i64::MAX]
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_deserialize_overflow() {
+        let result = from_value::<Foo>(json!({
+            "bar": [i64::MAX, 1]
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_deserialize_untrusted_length() {
+        // The length prefix claims far more elements than could ever be allocated.
+        let input = 0x0fff_ffff_ffff_ffff_u64.to_le_bytes();
+        assert!(bincode::deserialize::<Foo>(&input).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+//! Serializes an iterator of serializables into a flat serde sequence
+//! alternating between indices and elements.
+//!
+//! The output is `[0, v0, 1, v1, ...]`,
+//! as expected by some legacy protocols.
+//! This differs from serializing `(index, value)` pairs,
+//! which would produce nested sequences.
+//!
+//! *This module requires the "flat_indexed" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::flat_indexed")]
+//!     bar: Vec<&'static str>,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: vec!["a", "b"],
+//! };
+//! assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+//!     "bar": [0, "a", 1, "b"]
+//! }));
+//! ```
+
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0.saturating_mul(2)))?;
+    for (index, value) in iter.enumerate() {
+        seq.serialize_element(&index)?;
+        seq.serialize_element(&value)?;
+    }
+    seq.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde_json::{json, to_value};
+
+    #[derive(Serialize)]
+    struct Foo<T>
+    where
+        T: IntoIterator<Item = &'static str> + Clone,
+    {
+        #[serde(with = "super")]
+        bar: T,
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo { bar: vec![] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": []
+            })
+        );
+    }
+
+    #[test]
+    fn test_flat_indexed() {
+        let value = to_value(Foo {
+            bar: vec!["a", "b"],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": [0, "a", 1, "b"]
+            })
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! Writes an iterator of bytes as length-prefixed base64 frames.
+//!
+//! The bytes are split into frames of `frame` bytes each (the last frame may be shorter),
+//! and each frame is encoded with the standard padded base64 alphabet and written as
+//! `{len}:{payload}\n`, where `len` is the number of characters in `payload`.
+//! This is useful for streaming binary protocols over text transports.
+//!
+//! Unlike other modules, this writes to an `io::Write` directly rather than through a serde
+//! serializer.
+//!
+//! *This module requires the "frames" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! let mut buf = Vec::new();
+//! serde_iter::frames::to_writer(&mut buf, &b"hello".iter().copied(), 3).unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), "4:aGVs\n4:bG8=\n");
+//! ```
+
+use std::io::{self, Write};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+/// Refer to the [module-level documentation](index.html).
+///
+/// Returns an `InvalidInput` error if `frame` is zero.
+pub fn to_writer<W, T>(mut writer: W, iter: &T, frame: usize) -> io::Result<()>
+where
+    W: Write,
+    T: IntoIterator<Item = u8> + Clone,
+{
+    if frame == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "frame size must be nonzero",
+        ));
+    }
+
+    let mut iter = iter.clone().into_iter();
+    let mut buffer = Vec::with_capacity(frame);
+    let mut payload = String::new();
+    loop {
+        buffer.clear();
+        buffer.extend(iter.by_ref().take(frame));
+        if buffer.is_empty() {
+            break;
+        }
+
+        payload.clear();
+        STANDARD.encode_string(&buffer, &mut payload);
+        writeln!(writer, "{}:{}", payload.len(), payload)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_two_frames() {
+        let mut buf = Vec::new();
+        super::to_writer(&mut buf, &(0..6_u8), 4).expect("Failed to write");
+        let output = String::from_utf8(buf).expect("Output is not UTF-8");
+
+        let frames: Vec<_> = output.lines().collect();
+        assert_eq!(frames, vec!["8:AAECAw==", "4:BAU="]);
+        for frame in frames {
+            let (len, payload) = frame.split_once(':').expect("Missing length prefix");
+            assert_eq!(len.parse::<usize>().ok(), Some(payload.len()));
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let mut buf = Vec::new();
+        super::to_writer(&mut buf, &(0..0_u8), 4).expect("Failed to write");
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_zero_frame() {
+        let mut buf = Vec::new();
+        assert!(super::to_writer(&mut buf, &(0..6_u8), 0).is_err());
+    }
+}
@@ -0,0 +1,87 @@
+//! Serializes an iterator of serializables into a serde map from each distinct element
+//! to the number of times it occurs.
+//!
+//! This collapses a sequence into a frequency object, like a word count.
+//! Elements are emitted in the order of their first appearance.
+//!
+//! *This module requires the "frequencies" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::frequencies")]
+//!     bar: Vec<&'static str>,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: vec!["a", "b", "a"],
+//! };
+//! assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+//!     "bar": {"a": 2, "b": 1}
+//! }));
+//! ```
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize + Eq + Hash,
+{
+    let mut counts: HashMap<V, (usize, usize)> = HashMap::new();
+    for (index, value) in iter.clone().into_iter().enumerate() {
+        let (_, count) = counts.entry(value).or_insert((index, 0));
+        *count += 1;
+    }
+
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by_key(|&(_, (first, _))| first);
+
+    let mut map = serializer.serialize_map(Some(counts.len()))?;
+    for (value, (_, count)) in &counts {
+        map.serialize_entry(value, count)?;
+    }
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde_json::{json, to_string, to_value};
+
+    #[derive(Serialize)]
+    struct Foo<T>
+    where
+        T: IntoIterator<Item = &'static str> + Clone,
+    {
+        #[serde(with = "super")]
+        bar: T,
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo { bar: vec![] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {}
+            })
+        );
+    }
+
+    #[test]
+    fn test_frequencies() {
+        let json = to_string(&Foo {
+            bar: vec!["b", "a", "b", "c", "a", "b"],
+        });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(json, r#"{"bar":{"b":3,"a":2,"c":1}}"#);
+    }
+}
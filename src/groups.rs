@@ -0,0 +1,56 @@
+//! Groups the values of 2-tuples by key, in the order of first appearance.
+
+#[cfg(feature = "map")]
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+#[cfg(feature = "multimap")]
+use core::hash::Hash;
+#[cfg(feature = "multimap")]
+use std::collections::HashMap;
+
+/// A map used to look up the group of a key while grouping.
+pub(crate) trait GroupMap<K, V>:
+    Default + IntoIterator<Item = (K, (usize, Vec<V>))>
+{
+    /// Appends `value` to the group of `key`, creating it at `index` if it does not exist.
+    fn push(&mut self, index: usize, key: K, value: V);
+}
+
+#[cfg(feature = "map")]
+impl<K: Ord, V> GroupMap<K, V> for BTreeMap<K, (usize, Vec<V>)> {
+    fn push(&mut self, index: usize, key: K, value: V) {
+        self.entry(key)
+            .or_insert_with(|| (index, Vec::new()))
+            .1
+            .push(value);
+    }
+}
+
+#[cfg(feature = "multimap")]
+impl<K: Eq + Hash, V> GroupMap<K, V> for HashMap<K, (usize, Vec<V>)> {
+    fn push(&mut self, index: usize, key: K, value: V) {
+        self.entry(key)
+            .or_insert_with(|| (index, Vec::new()))
+            .1
+            .push(value);
+    }
+}
+
+/// Collects the values of each key into a group,
+/// with groups sorted by the first appearance of their key.
+pub(crate) fn group<M, K, V, I>(iter: I) -> Vec<(K, Vec<V>)>
+where
+    M: GroupMap<K, V>,
+    I: IntoIterator<Item = (K, V)>,
+{
+    let mut groups = M::default();
+    for (index, (key, value)) in iter.into_iter().enumerate() {
+        groups.push(index, key, value);
+    }
+    let mut groups: Vec<_> = groups.into_iter().collect();
+    groups.sort_by_key(|(_, (index, _))| *index);
+    groups
+        .into_iter()
+        .map(|(key, (_, values))| (key, values))
+        .collect()
+}
@@ -0,0 +1,137 @@
+//! Serializes an iterator of `i64` into a histogram map.
+//!
+//! Each value is bucketed into a bin of the given width,
+//! and the output is a map from the lower bound of each non-empty bin to the number of values in
+//! it, in ascending order of bins.
+//! Bins are aligned to multiples of the width, so a value `v` is counted in the bin
+//! `floor(v / width) * width`.
+//!
+//! *This module requires the "histogram" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! fn histogram<S: serde::Serializer>(iter: &[i64; 5], serializer: S) -> Result<S::Ok, S::Error> {
+//!     serde_iter::histogram::serialize(iter, 5, serializer)
+//! }
+//!
+//! #[derive(serde::Serialize)]
+//! struct Foo {
+//!     #[serde(serialize_with = "histogram")]
+//!     bar: [i64; 5],
+//! }
+//!
+//! let foo = Foo {
+//!     bar: [1, 2, 2, 3, 10],
+//! };
+//! assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+//!     "bar": {"0": 4, "10": 1}
+//! }));
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::ser::{Error, Serialize, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+///
+/// Returns an error if `width` is not positive,
+/// or if the bin of a value is out of the range of `i64`.
+pub fn serialize<S, T>(iter: &T, width: i64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = i64> + Clone,
+{
+    if width <= 0 {
+        return Err(S::Error::custom("histogram bin width must be positive"));
+    }
+
+    let mut bins = BTreeMap::new();
+    for value in iter.clone() {
+        let bin = value
+            .div_euclid(width)
+            .checked_mul(width)
+            .ok_or_else(|| S::Error::custom(format_args!("bin of {} overflows i64", value)))?;
+        *bins.entry(bin).or_insert(0_usize) += 1;
+    }
+    bins.serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_value};
+
+    fn width_five<S: Serializer>(iter: &[i64; 5], serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, 5, serializer)
+    }
+
+    fn width_three<S: Serializer>(iter: &[i64; 5], serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, 3, serializer)
+    }
+
+    fn width_zero<S: Serializer>(iter: &[i64; 5], serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, 0, serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Five {
+        #[serde(serialize_with = "width_five")]
+        bar: [i64; 5],
+    }
+
+    #[derive(Serialize)]
+    struct Three {
+        #[serde(serialize_with = "width_three")]
+        bar: [i64; 5],
+    }
+
+    #[derive(Serialize)]
+    struct Zero {
+        #[serde(serialize_with = "width_zero")]
+        bar: [i64; 5],
+    }
+
+    #[test]
+    fn test_histogram() {
+        let value = to_value(Five {
+            bar: [1, 2, 2, 3, 10],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"0": 4, "10": 1}
+            })
+        );
+    }
+
+    #[test]
+    fn test_negative() {
+        let value = to_value(Five {
+            bar: [-1, -5, -6, 0, 4],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"-10": 1, "-5": 2, "0": 2}
+            })
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        let result = to_value(Three {
+            bar: [i64::MIN, 0, 0, 0, 0],
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_zero_width() {
+        let result = to_value(Zero {
+            bar: [1, 2, 2, 3, 10],
+        });
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! Serializes an iterator of serializables into a JSON Patch (RFC 6902) document
+//! of `add` operations.
+//!
+//! Each element becomes an operation `{"op": "add", "path": "<base>/<index>", "value": ...}`,
+//! where `base` is a JSON Pointer to the target array and `index` is the position of the element.
+//! Applying the patch to a document containing an empty array at `base`
+//! fills the array with the elements in order.
+//! This supports generating patch documents from lazy data.
+//!
+//! *This module requires the "json" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! let mut buf = Vec::new();
+//! serde_iter::json_patch::serialize(
+//!     &vec!["a"],
+//!     "/tags",
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(
+//!     String::from_utf8(buf).unwrap(),
+//!     r#"[{"op":"add","path":"/tags/0","value":"a"}]"#,
+//! );
+//! ```
+
+use serde::ser::{Serialize, SerializeSeq, SerializeStruct, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, V>(iter: &T, base_path: &str, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    for (index, value) in iter.enumerate() {
+        seq.serialize_element(&AddOperation {
+            base_path,
+            index,
+            value: &value,
+        })?;
+    }
+    seq.end()
+}
+
+struct AddOperation<'a, V> {
+    base_path: &'a str,
+    index: usize,
+    value: &'a V,
+}
+
+impl<'a, V> Serialize for AddOperation<'a, V>
+where
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut st = serializer.serialize_struct("AddOperation", 3)?;
+        st.serialize_field("op", "add")?;
+        st.serialize_field("path", &format_args!("{}/{}", self.base_path, self.index))?;
+        st.serialize_field("value", self.value)?;
+        st.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_value};
+
+    type Values = [i32; 2];
+
+    fn patch_items<S: Serializer>(iter: &Values, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, "/base", serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(serialize_with = "patch_items")]
+        bar: Values,
+    }
+
+    #[test]
+    fn test_patch() {
+        let value = to_value(Foo { bar: [5, 7] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": [
+                    {"op": "add", "path": "/base/0", "value": 5},
+                    {"op": "add", "path": "/base/1", "value": 7}
+                ]
+            })
+        );
+    }
+}
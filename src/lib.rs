@@ -10,6 +10,37 @@
 //! This crate provides serializer functions to serialize iterator types as sequences and maps.
 //!
 //! See the documentation in each module for details.
+//!
+//! # Functions with extra arguments
+//! Many functions take arguments besides the iterator and the serializer,
+//! such as a window size, a predicate or a key function.
+//! Serde calls `#[serde(with)]` and `#[serde(serialize_with)]` functions
+//! with only the field and the serializer,
+//! so such functions are called from a small wrapper function with that signature instead:
+//!
+//! ```
+//! #[derive(serde::Serialize)]
+//! struct Foo {
+//!     #[serde(serialize_with = "first_two")]
+//!     bar: Vec<u32>,
+//! }
+//!
+//! fn first_two<S: serde::Serializer>(bar: &Vec<u32>, serializer: S) -> Result<S::Ok, S::Error> {
+//!     serde_iter::seq::serialize_take(bar, 2, serializer)
+//! }
+//!
+//! let foo = Foo { bar: vec![1, 2, 3] };
+//! assert_eq!(serde_json::to_string(&foo).unwrap(), r#"{"bar":[1,2]}"#);
+//! ```
+//! Only the "seq", "map" and "once" features are enabled by default;
+//! the other modules are opt-in through the feature named in their documentation.
+//!
+//! # `no_std` support
+//! The "seq", "map" and "once" features work without the standard library.
+//! Disable the default "std" feature to build in `no_std` mode,
+//! and enable the "alloc" feature for the functions that need to allocate,
+//! such as the `deserialize` functions.
+//! The other modules require the "std" feature.
 
 #![warn(
     unused_qualifications,
@@ -17,7 +48,7 @@
     clippy::checked_conversions,
     clippy::needless_borrow,
     clippy::shadow_unrelated,
-    clippy::wrong_pub_self_convention
+    clippy::wrong_self_convention
 )]
 #![deny(
     anonymous_parameters,
@@ -27,8 +58,7 @@
     clippy::float_cmp_const,
     clippy::if_not_else,
     clippy::indexing_slicing,
-    clippy::option_unwrap_used,
-    clippy::result_unwrap_used
+    clippy::unwrap_used
 )]
 #![cfg_attr(
     debug_assertions,
@@ -41,14 +71,188 @@
     )
 )]
 #![cfg_attr(not(debug_assertions), deny(warnings, missing_docs, clippy::dbg_macro))]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "bits")]
+pub mod bits;
+
+#[cfg(feature = "bloom")]
+pub mod bloom_unique;
+
+#[cfg(feature = "checkpointed")]
+pub mod checkpointed;
+
+#[cfg(feature = "content_map")]
+pub mod content_map;
+
+#[cfg(feature = "dict")]
+pub mod dict;
+
+#[cfg(feature = "dod")]
+pub mod dod;
+
+#[cfg(feature = "flat_indexed")]
+pub mod flat_indexed;
+
+#[cfg(feature = "frames")]
+pub mod frames;
+
+#[cfg(feature = "frequencies")]
+pub mod frequencies;
+
+#[cfg(feature = "histogram")]
+pub mod histogram;
+
+#[cfg(feature = "json")]
+pub mod json_patch;
 
 #[cfg(feature = "map")]
 pub mod map;
 
+#[cfg(feature = "map_bounded")]
+pub mod map_bounded;
+
+#[cfg(feature = "map_by_value_hash")]
+pub mod map_by_value_hash;
+
+#[cfg(feature = "map_clamp")]
+pub mod map_clamp;
+
+#[cfg(feature = "collation")]
+pub mod map_collated;
+
+#[cfg(feature = "map_diff")]
+pub mod map_diff;
+
+#[cfg(feature = "map_dual_key")]
+pub mod map_dual_key;
+
+#[cfg(feature = "map_expiring")]
+pub mod map_expiring;
+
+#[cfg(feature = "map_flatten_keys")]
+pub mod map_flatten_keys;
+
+#[cfg(feature = "map_grouped")]
+pub mod map_grouped;
+
+#[cfg(feature = "map_overlay")]
+pub mod map_overlay;
+
+#[cfg(feature = "json")]
+pub mod map_raw;
+
+#[cfg(feature = "map_redact")]
+pub mod map_redact;
+
+#[cfg(feature = "map_reverse")]
+pub mod map_reverse;
+
+#[cfg(feature = "map_round")]
+pub mod map_round;
+
+#[cfg(feature = "map_shared")]
+pub mod map_shared;
+
+#[cfg(feature = "map_sorted_by_value_then_key")]
+pub mod map_sorted_by_value_then_key;
+
+#[cfg(feature = "map_tree")]
+pub mod map_tree;
+
+#[cfg(feature = "map_truncate_values")]
+pub mod map_truncate_values;
+
+#[cfg(feature = "map_typed")]
+pub mod map_typed;
+
+#[cfg(feature = "merkle")]
+pub mod merkle;
+
+#[cfg(feature = "moving_average")]
+pub mod moving_average;
+
+#[cfg(feature = "multimap")]
+pub mod multimap;
+
+#[cfg(feature = "nested")]
+pub mod nested;
+
+#[cfg(feature = "pages")]
+pub mod pages;
+
+#[cfg(feature = "paginated")]
+pub mod paginated;
+
+#[cfg(feature = "percentiles")]
+pub mod percentiles;
+
+#[cfg(feature = "json")]
+pub mod project;
+
+#[cfg(feature = "ranges")]
+pub mod ranges;
+
 #[cfg(feature = "seq")]
 pub mod seq;
 
+#[cfg(feature = "seq_changes")]
+pub mod seq_changes;
+
+#[cfg(feature = "seq_display")]
+pub mod seq_display;
+
+#[cfg(feature = "seq_validated")]
+pub mod seq_validated;
+
+#[cfg(feature = "set")]
+pub mod set;
+
+#[cfg(feature = "sparse")]
+pub mod sparse;
+
+#[cfg(feature = "split")]
+pub mod split;
+
+#[cfg(feature = "thin")]
+pub mod thin;
+
+#[cfg(feature = "timed")]
+pub mod timed;
+
+#[cfg(feature = "tuple")]
+pub mod tuple;
+
+#[cfg(feature = "verbosity")]
+pub mod verbosity;
+
+#[cfg(all(not(feature = "std"), any(feature = "seq", feature = "map")))]
+mod compile_test;
+
+#[cfg(any(
+    feature = "content_map",
+    feature = "map_by_value_hash",
+    feature = "merkle"
+))]
+mod digest;
+
+#[cfg(all(feature = "alloc", any(feature = "map", feature = "multimap")))]
+mod groups;
+
 #[cfg(feature = "once")]
 mod once;
+#[cfg(all(feature = "once", feature = "alloc"))]
+pub use once::BoxedCloneOnce;
 #[cfg(feature = "once")]
-pub use once::CloneOnce;
+pub use once::{CloneOnce, CloneOnceIter};
+
+#[cfg(feature = "reusable")]
+mod reusable;
+#[cfg(feature = "reusable")]
+pub use reusable::{Replay, ReusableIter};
+
+#[cfg(feature = "alloc")]
+mod size_hint;
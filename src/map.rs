@@ -3,6 +3,7 @@
 //! This module is motsly identical to `serde_iter::seq`, except that iterators of tuples `(K, V)`
 //! instead of single values are used.
 //! Refer to the [`serde_iter::seq`](../seq/index.html) documentation for details.
+//! Similarly, [`AsMap`] wraps an iterator to serialize it as a map without `#[serde(with)]`.
 //!
 //! *This module requires the "map" feature to be enabled (enabled by default).*
 //!
@@ -24,7 +25,22 @@
 //! }));
 //! ```
 
-use serde::ser::{Serialize, SerializeMap, Serializer};
+#[cfg(feature = "alloc")]
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::fmt::Display;
+#[cfg(feature = "alloc")]
+use core::{fmt, iter::FromIterator, marker::PhantomData};
+#[cfg(feature = "std")]
+use core::{
+    fmt::Debug,
+    hash::{BuildHasher, Hash},
+};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "alloc")]
+use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use serde::ser::{Error, Serialize, SerializeMap, SerializeSeq, Serializer};
 
 /// Refer to the [module-level documentation](index.html).
 pub fn serialize<S, T, K, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
@@ -42,11 +58,1044 @@ where
     map.end()
 }
 
+/// A wrapper that serializes an iterator of 2-tuples into a serde map.
+///
+/// This is the map counterpart of [`seq::AsSeq`](../seq/struct.AsSeq.html),
+/// and is equivalent to [`serialize`](fn.serialize.html)
+/// without requiring `#[serde(with)]`.
+///
+/// # Example
+/// ```
+/// let value = serde_json::json!({
+///     "name": "foo",
+///     "counts": serde_iter::map::AsMap(vec![("a", 1), ("b", 2)]),
+/// });
+/// assert_eq!(value, serde_json::json!({
+///     "name": "foo",
+///     "counts": {"a": 1, "b": 2},
+/// }));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AsMap<T>(pub T);
+
+impl<T, K, V> Serialize for AsMap<T>
+where
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(&self.0, serializer)
+    }
+}
+
+/// Serializes a collection of keys into a serde map,
+/// with each value computed lazily from its key at serialization time.
+///
+/// The value function is called once per key on *every* serialization,
+/// since values are not cached.
+/// If the value function is expensive and the map is serialized multiple times,
+/// consider collecting the values beforehand.
+///
+/// # Example
+/// ```
+/// let lazy = serde_iter::map::LazyValues::new(vec!["a", "bb"], |key: &&str| key.len());
+/// assert_eq!(serde_json::to_value(&lazy).unwrap(), serde_json::json!({
+///     "a": 1,
+///     "bb": 2,
+/// }));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LazyValues<K, F> {
+    keys: K,
+    f: F,
+}
+
+impl<K, F> LazyValues<K, F> {
+    /// Creates a map from `keys`, computing the value of each key with `f`.
+    pub fn new(keys: K, f: F) -> Self {
+        Self { keys, f }
+    }
+}
+
+impl<K, F, V> Serialize for LazyValues<K, F>
+where
+    K: IntoIterator + Clone,
+    K::Item: Serialize,
+    F: Fn(&K::Item) -> V,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let iter = self.keys.clone().into_iter();
+        let mut map = serializer.serialize_map(Some(iter.size_hint().0))?;
+        for key in iter {
+            let value = (self.f)(&key);
+            map.serialize_entry(&key, &value)?;
+        }
+        map.end()
+    }
+}
+
+/// Serializes at most a fixed number of entries from an iterator of 2-tuples into a serde map,
+/// reporting the number of skipped entries under an `"_omitted"` key.
+///
+/// The `"_omitted"` entry is only emitted if some entries were skipped.
+/// The remaining entries are still iterated over to count them, but they are not serialized.
+///
+/// # Example
+/// ```
+/// let capped = serde_iter::map::CappedWithCount::new(vec![("a", 1), ("b", 2), ("c", 3)], 2);
+/// assert_eq!(serde_json::to_value(&capped).unwrap(), serde_json::json!({
+///     "a": 1,
+///     "b": 2,
+///     "_omitted": 1,
+/// }));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CappedWithCount<I> {
+    iter: I,
+    cap: usize,
+}
+
+impl<I> CappedWithCount<I> {
+    /// Creates a map that serializes at most `cap` entries from `iter`.
+    pub fn new(iter: I, cap: usize) -> Self {
+        Self { iter, cap }
+    }
+}
+
+impl<I, K, V> Serialize for CappedWithCount<I>
+where
+    I: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut iter = self.iter.clone().into_iter();
+        let mut map = serializer.serialize_map(None)?;
+        for (key, value) in iter.by_ref().take(self.cap) {
+            map.serialize_entry(&key, &value)?;
+        }
+        let omitted = iter.count();
+        if omitted > 0 {
+            map.serialize_entry("_omitted", &omitted)?;
+        }
+        map.end()
+    }
+}
+
+/// Serializes an iterator of serializables into a serde map,
+/// with each key computed from its value by a fallible function.
+///
+/// The serialization fails with the error message of the first key that cannot be computed.
+///
+/// # Example
+/// ```
+/// let keyed = serde_iter::map::TryKeyedBy::new(vec!["1:foo", "2:bar"], |value: &&str| {
+///     value.split(':').next().unwrap_or_default().parse::<u32>()
+/// });
+/// assert_eq!(serde_json::to_value(&keyed).unwrap(), serde_json::json!({
+///     "1": "1:foo",
+///     "2": "2:bar",
+/// }));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TryKeyedBy<I, F> {
+    iter: I,
+    f: F,
+}
+
+impl<I, F> TryKeyedBy<I, F> {
+    /// Creates a map from the values in `iter`, computing the key of each value with `f`.
+    pub fn new(iter: I, f: F) -> Self {
+        Self { iter, f }
+    }
+}
+
+impl<I, F, K, E> Serialize for TryKeyedBy<I, F>
+where
+    I: IntoIterator + Clone,
+    I::Item: Serialize,
+    F: Fn(&I::Item) -> Result<K, E>,
+    K: Serialize,
+    E: Display,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let iter = self.iter.clone().into_iter();
+        let mut map = serializer.serialize_map(Some(iter.size_hint().0))?;
+        for value in iter {
+            let key = (self.f)(&value).map_err(S::Error::custom)?;
+            map.serialize_entry(&key, &value)?;
+        }
+        map.end()
+    }
+}
+
+/// Serializes an iterator of 2-tuples into a serde map,
+/// appending an entry that summarizes all values.
+///
+/// The summary value is computed by reducing all values with the fold function,
+/// similar to `Iterator::reduce`, and is emitted under the summary key after all other entries.
+/// If the iterator is empty, the summary entry is omitted.
+///
+/// This iterates over two clones of the iterator:
+/// one for the entries and one for the summary.
+///
+/// # Example
+/// ```
+/// let totals = serde_iter::map::WithSummary::new(
+///     vec![("a", 1), ("b", 2)],
+///     "_total",
+///     |sum, value| sum + value,
+/// );
+/// assert_eq!(serde_json::to_value(&totals).unwrap(), serde_json::json!({
+///     "a": 1,
+///     "b": 2,
+///     "_total": 3,
+/// }));
+/// ```
+#[derive(Debug, Clone)]
+pub struct WithSummary<I, F, Q = &'static str> {
+    iter: I,
+    summary_key: Q,
+    fold_fn: F,
+}
+
+impl<I, F, Q> WithSummary<I, F, Q> {
+    /// Creates a map from `iter`,
+    /// with the values reduced by `fold_fn` appended under `summary_key`.
+    pub fn new(iter: I, summary_key: Q, fold_fn: F) -> Self {
+        Self {
+            iter,
+            summary_key,
+            fold_fn,
+        }
+    }
+}
+
+impl<I, F, Q, K, V> Serialize for WithSummary<I, F, Q>
+where
+    I: IntoIterator<Item = (K, V)> + Clone,
+    F: Fn(V, V) -> V,
+    Q: Serialize,
+    K: Serialize,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let iter = self.iter.clone().into_iter();
+        let lower = iter.size_hint().0;
+        // The summary entry is only emitted if there is at least one entry.
+        let len = lower.saturating_add(usize::from(lower > 0));
+        let mut map = serializer.serialize_map(Some(len))?;
+        for (key, value) in iter {
+            map.serialize_entry(&key, &value)?;
+        }
+        let summary = self.iter.clone().into_iter().map(|(_, value)| value).fold(
+            None,
+            |acc, value| match acc {
+                Some(acc) => Some((self.fold_fn)(acc, value)),
+                None => Some(value),
+            },
+        );
+        if let Some(summary) = summary {
+            map.serialize_entry(&self.summary_key, &summary)?;
+        }
+        map.end()
+    }
+}
+
+/// Serializes an iterator of 2-tuples into a serde map,
+/// with each key transformed by a normalization function (e.g. lowercasing).
+///
+/// Normalization may map different keys to the same normalized key.
+/// A map created with [`new`](#method.new) only emits the first entry of each normalized key,
+/// while a map created with [`strict`](#method.strict) fails the serialization instead.
+///
+/// # Example
+/// ```
+/// let normalized = serde_iter::map::NormalizeKeys::new(
+///     vec![("Foo", 1), ("Bar", 2), ("foo", 3)],
+///     |key: &&str| key.to_lowercase(),
+/// );
+/// assert_eq!(serde_json::to_value(&normalized).unwrap(), serde_json::json!({
+///     "foo": 1,
+///     "bar": 2,
+/// }));
+///
+/// let strict = serde_iter::map::NormalizeKeys::strict(
+///     vec![("Foo", 1), ("foo", 3)],
+///     |key: &&str| key.to_lowercase(),
+/// );
+/// assert!(serde_json::to_value(&strict).is_err());
+/// ```
+///
+/// *This struct requires the "std" feature to be enabled (enabled by default).*
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct NormalizeKeys<I, F> {
+    iter: I,
+    f: F,
+    strict: bool,
+}
+
+#[cfg(feature = "std")]
+impl<I, F> NormalizeKeys<I, F> {
+    /// Creates a map from `iter` with keys normalized by `f`,
+    /// keeping the first entry of colliding normalized keys.
+    pub fn new(iter: I, f: F) -> Self {
+        Self {
+            iter,
+            f,
+            strict: false,
+        }
+    }
+
+    /// Creates a map from `iter` with keys normalized by `f`,
+    /// failing the serialization if normalized keys collide.
+    pub fn strict(iter: I, f: F) -> Self {
+        Self {
+            iter,
+            f,
+            strict: true,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I, F, K, V, N> Serialize for NormalizeKeys<I, F>
+where
+    I: IntoIterator<Item = (K, V)> + Clone,
+    F: Fn(&K) -> N,
+    V: Serialize,
+    N: Serialize + Eq + Hash + Debug,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let iter = self.iter.clone().into_iter();
+        let mut map = serializer.serialize_map(None)?;
+        let mut seen = HashSet::new();
+        for (key, value) in iter {
+            let key = (self.f)(&key);
+            if seen.contains(&key) {
+                if self.strict {
+                    return Err(S::Error::custom(format_args!(
+                        "duplicate normalized key {:?}",
+                        key
+                    )));
+                }
+                continue;
+            }
+            map.serialize_entry(&key, &value)?;
+            seen.insert(key);
+        }
+        map.end()
+    }
+}
+
+/// Serializes an iterator of serializables into a serde map,
+/// with each key computed from its value and a borrowed context.
+///
+/// This is useful when keys are resolved through an external object, such as a symbol table.
+/// Passing the context separately instead of capturing it in the key function
+/// keeps the key function a plain `fn` or non-capturing closure,
+/// and the wrapper remains `Clone` regardless of whether the context type is.
+///
+/// # Example
+/// ```
+/// use std::collections::HashMap;
+///
+/// let mut names = HashMap::new();
+/// names.insert(1, "main");
+///
+/// let keyed = serde_iter::map::KeyedWithContext::new(
+///     vec![1],
+///     &names,
+///     |names: &HashMap<i32, &'static str>, id: &i32| names.get(id).copied().unwrap_or("?"),
+/// );
+/// assert_eq!(serde_json::to_value(&keyed).unwrap(), serde_json::json!({
+///     "main": 1,
+/// }));
+/// ```
+#[derive(Debug)]
+pub struct KeyedWithContext<'a, I, C, F> {
+    iter: I,
+    context: &'a C,
+    f: F,
+}
+
+impl<'a, I, C, F> KeyedWithContext<'a, I, C, F> {
+    /// Creates a map from the values in `iter`,
+    /// computing the key of each value with `f` and the borrowed `context`.
+    pub fn new(iter: I, context: &'a C, f: F) -> Self {
+        Self { iter, context, f }
+    }
+}
+
+impl<'a, I, C, F> Clone for KeyedWithContext<'a, I, C, F>
+where
+    I: Clone,
+    F: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            iter: self.iter.clone(),
+            context: self.context,
+            f: self.f.clone(),
+        }
+    }
+}
+
+impl<'a, I, C, F, K> Serialize for KeyedWithContext<'a, I, C, F>
+where
+    I: IntoIterator + Clone,
+    I::Item: Serialize,
+    F: Fn(&C, &I::Item) -> K,
+    K: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let iter = self.iter.clone().into_iter();
+        let mut map = serializer.serialize_map(Some(iter.size_hint().0))?;
+        for value in iter {
+            let key = (self.f)(self.context, &value);
+            map.serialize_entry(&key, &value)?;
+        }
+        map.end()
+    }
+}
+
+/// Serializes an iterator of serializable 2-tuples into a serde map, sorted by key.
+///
+/// The entries are collected and sorted before being serialized,
+/// so the output is deterministic even if the iteration order is not, e.g. for a `HashMap`.
+///
+/// If several entries have equal keys, only the last of them in iteration order is emitted,
+/// matching the semantics of inserting the entries into a map one by one.
+///
+/// # Example
+/// ```
+/// use std::collections::HashMap;
+///
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "serde_iter::map::serialize_sorted")]
+///     bar: HashMap<&'static str, i32>,
+/// }
+///
+/// let mut bar = HashMap::new();
+/// bar.insert("b", 2);
+/// bar.insert("a", 1);
+/// assert_eq!(
+///     serde_json::to_string(&Foo { bar }).unwrap(),
+///     r#"{"bar":{"a":1,"b":2}}"#,
+/// );
+/// ```
+///
+/// *This function requires the "alloc" feature to be enabled (enabled by default).*
+#[cfg(feature = "alloc")]
+pub fn serialize_sorted<S, T, K, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize + Ord,
+    V: Serialize,
+{
+    let mut entries: Vec<(K, V)> = iter.clone().into_iter().collect();
+    // The sort is stable, so entries with equal keys stay in iteration order.
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut map = serializer.serialize_map(None)?;
+    let mut entries = entries.iter().peekable();
+    while let Some((key, value)) = entries.next() {
+        let overridden = entries.peek().is_some_and(|(next, _)| next == key);
+        if !overridden {
+            map.serialize_entry(key, value)?;
+        }
+    }
+    map.end()
+}
+
+/// Deserializes a serde map into any `FromIterator` container of 2-tuples.
+///
+/// This allows `#[serde(with = "serde_iter::map")]` on fields that are also deserialized,
+/// such as a `BTreeMap` or a `Vec<(K, V)>`.
+/// The entries are passed to `FromIterator` in the order they appear in the input,
+/// so duplicate keys are handled by the container, e.g. the last value wins for a `HashMap`.
+///
+/// # Example
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Foo {
+///     #[serde(with = "serde_iter::map")]
+///     bar: BTreeMap<String, i32>,
+/// }
+///
+/// let foo: Foo = serde_json::from_str(r#"{"bar":{"a":1}}"#).unwrap();
+/// assert_eq!(foo.bar.get("a"), Some(&1));
+/// ```
+///
+/// *This function requires the "alloc" feature to be enabled (enabled by default).*
+#[cfg(feature = "alloc")]
+pub fn deserialize<'de, D, C, K, V>(deserializer: D) -> Result<C, D::Error>
+where
+    D: Deserializer<'de>,
+    C: FromIterator<(K, V)>,
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    deserializer.deserialize_map(MapVisitor(PhantomData))
+}
+
+#[cfg(feature = "alloc")]
+struct MapVisitor<C, K, V>(PhantomData<(C, K, V)>);
+
+#[cfg(feature = "alloc")]
+impl<'de, C, K, V> Visitor<'de> for MapVisitor<C, K, V>
+where
+    C: FromIterator<(K, V)>,
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = C;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<C, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries = Vec::with_capacity(crate::size_hint::cautious::<(K, V)>(map.size_hint()));
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(entries.into_iter().collect())
+    }
+}
+
+/// Deserializes a serde map into an existing `HashMap`.
+///
+/// The entries are inserted into `target` without clearing it first,
+/// so entries from previous calls are kept unless they are overwritten by an equal key.
+/// Call `target.clear()` beforehand to replace the contents instead;
+/// either way, the allocation of `target` is reused across calls,
+/// which is useful for deserializing many messages in a hot loop.
+///
+/// Serde has no way to hand an existing collection to `#[serde(deserialize_with)]`,
+/// so this function is called from a manual `Deserialize` implementation
+/// or directly with a `Deserializer`.
+///
+/// *This function requires the "std" feature to be enabled (enabled by default).*
+///
+/// # Example
+/// ```
+/// use std::collections::HashMap;
+///
+/// let mut target: HashMap<String, i32> = HashMap::new();
+/// for message in [r#"{"a":1,"b":2}"#, r#"{"b":3}"#] {
+///     let mut deserializer = serde_json::Deserializer::from_str(message);
+///     serde_iter::map::deserialize_into(&mut target, &mut deserializer).unwrap();
+/// }
+/// assert_eq!(target.get("a"), Some(&1));
+/// assert_eq!(target.get("b"), Some(&3));
+/// ```
+#[cfg(feature = "std")]
+pub fn deserialize_into<'de, D, K, V, H>(
+    target: &mut HashMap<K, V, H>,
+    deserializer: D,
+) -> Result<(), D::Error>
+where
+    D: Deserializer<'de>,
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    H: BuildHasher,
+{
+    deserializer.deserialize_map(IntoVisitor(target))
+}
+
+#[cfg(feature = "std")]
+struct IntoVisitor<'a, K, V, H>(&'a mut HashMap<K, V, H>);
+
+#[cfg(feature = "std")]
+impl<'a, 'de, K, V, H> Visitor<'de> for IntoVisitor<'a, K, V, H>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    H: BuildHasher,
+{
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<(), A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.0
+            .reserve(crate::size_hint::cautious::<(K, V)>(map.size_hint()));
+        while let Some((key, value)) = map.next_entry()? {
+            self.0.insert(key, value);
+        }
+        Ok(())
+    }
+}
+
+/// Serializes an iterator of 2-tuples into a serde map, with keys serialized as strings.
+///
+/// Each key is serialized as its `Display` representation, while values are serialized normally.
+/// This is handy for exporting integer-keyed maps to consumers that expect string keys,
+/// and the keys only need to implement `Display`, not `Serialize`.
+///
+/// Note that formats with native support for non-string keys, such as MessagePack or CBOR,
+/// also receive string keys, so the keys do not round-trip to their original type there.
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "serde_iter::map::serialize_stringify")]
+///     bar: Vec<(std::net::Ipv4Addr, u32)>,
+/// }
+///
+/// let foo = Foo {
+///     bar: vec![(std::net::Ipv4Addr::LOCALHOST, 3)],
+/// };
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": {"127.0.0.1": 3}
+/// }));
+/// ```
+pub fn serialize_stringify<S, T, K, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Display,
+    V: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut map = serializer.serialize_map(Some(iter.size_hint().0))?;
+    for (key, value) in iter {
+        map.serialize_entry(&Stringified(&key), &value)?;
+    }
+    map.end()
+}
+
+struct Stringified<'a, K>(&'a K);
+
+impl<'a, K> Serialize for Stringified<'a, K>
+where
+    K: Display,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self.0)
+    }
+}
+
+/// Serializes the entries of an iterator of 2-tuples that satisfy a predicate into a serde map.
+///
+/// Entries for which the predicate returns `false` are skipped,
+/// without collecting the remaining entries into a filtered collection.
+/// Since the number of remaining entries is unknown in advance,
+/// the map length is passed to the serializer as `None`.
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "present")]
+///     bar: Vec<(&'static str, Option<u32>)>,
+/// }
+///
+/// fn present<S: serde::Serializer>(
+///     bar: &Vec<(&'static str, Option<u32>)>,
+///     serializer: S,
+/// ) -> Result<S::Ok, S::Error> {
+///     serde_iter::map::serialize_filtered(
+///         bar,
+///         |_: &&str, value: &Option<u32>| value.is_some(),
+///         serializer,
+///     )
+/// }
+///
+/// let foo = Foo {
+///     bar: vec![("a", Some(1)), ("b", None)],
+/// };
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": {"a": 1}
+/// }));
+/// ```
+pub fn serialize_filtered<S, T, K, V, F>(
+    iter: &T,
+    pred: F,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize,
+    V: Serialize,
+    F: Fn(&K, &V) -> bool + Clone,
+{
+    let iter = iter.clone().into_iter();
+    let mut map = serializer.serialize_map(None)?;
+    for (key, value) in iter {
+        if pred(&key, &value) {
+            map.serialize_entry(&key, &value)?;
+        }
+    }
+    map.end()
+}
+
+/// Serializes an iterator of 2-tuples with duplicate keys into a serde map of arrays.
+///
+/// The values of each key are collected into an array in their original order,
+/// and keys are emitted in the order of their first appearance.
+/// This is similar to the [`multimap`](../multimap/index.html) module,
+/// but only requires the keys to implement `Ord` instead of `Hash`.
+///
+/// *This function requires the "alloc" feature to be enabled (enabled by default).*
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "serde_iter::map::serialize_grouped")]
+///     bar: Vec<(&'static str, i32)>,
+/// }
+///
+/// let foo = Foo {
+///     bar: vec![("b", 1), ("a", 2), ("b", 3)],
+/// };
+/// assert_eq!(
+///     serde_json::to_string(&foo).unwrap(),
+///     r#"{"bar":{"b":[1,3],"a":[2]}}"#,
+/// );
+/// ```
+#[cfg(feature = "alloc")]
+pub fn serialize_grouped<S, T, K, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize + Ord,
+    V: Serialize,
+{
+    let groups = crate::groups::group::<BTreeMap<_, _>, _, _, _>(iter.clone());
+    let mut map = serializer.serialize_map(Some(groups.len()))?;
+    for (key, values) in &groups {
+        map.serialize_entry(key, values)?;
+    }
+    map.end()
+}
+
+/// Serializes an iterator of 2-tuples into a serde map, or `None` if the iterator is empty.
+///
+/// This is useful for schemas that disallow empty objects.
+/// The cloned iterator is peeked to check for emptiness,
+/// so no element is consumed twice.
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "serde_iter::map::serialize_null_if_empty")]
+///     bar: Vec<(&'static str, i32)>,
+/// }
+///
+/// assert_eq!(
+///     serde_json::to_value(&Foo { bar: vec![] }).unwrap(),
+///     serde_json::json!({"bar": null}),
+/// );
+/// assert_eq!(
+///     serde_json::to_value(&Foo { bar: vec![("a", 1)] }).unwrap(),
+///     serde_json::json!({"bar": {"a": 1}}),
+/// );
+/// ```
+pub fn serialize_null_if_empty<S, T, K, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize,
+    V: Serialize,
+{
+    let mut iter = iter.clone().into_iter().peekable();
+    if iter.peek().is_none() {
+        return serializer.serialize_none();
+    }
+
+    let mut map = serializer.serialize_map(Some(iter.size_hint().0))?;
+    for (key, value) in iter {
+        map.serialize_entry(&key, &value)?;
+    }
+    map.end()
+}
+
+/// Serializes two parallel iterators of keys and values into a serde map.
+///
+/// The iterators are zipped together,
+/// so if they have different lengths, the extra elements of the longer one are ignored.
+///
+/// # Example
+/// ```
+/// struct Table {
+///     columns: Vec<&'static str>,
+///     row: Vec<i32>,
+/// }
+///
+/// impl serde::Serialize for Table {
+///     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+///         serde_iter::map::serialize_zip(&self.columns, &self.row, serializer)
+///     }
+/// }
+///
+/// let table = Table {
+///     columns: vec!["id", "age"],
+///     row: vec![1, 42],
+/// };
+/// assert_eq!(serde_json::to_value(&table).unwrap(), serde_json::json!({
+///     "id": 1,
+///     "age": 42,
+/// }));
+/// ```
+pub fn serialize_zip<S, KI, VI, K, V>(
+    keys: &KI,
+    values: &VI,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    KI: IntoIterator<Item = K> + Clone,
+    VI: IntoIterator<Item = V> + Clone,
+    K: Serialize,
+    V: Serialize,
+{
+    let iter = keys.clone().into_iter().zip(values.clone());
+    let mut map = serializer.serialize_map(Some(iter.size_hint().0))?;
+    for (key, value) in iter {
+        map.serialize_entry(&key, &value)?;
+    }
+    map.end()
+}
+
+/// Serializes an iterator of serializables into a serde map keyed by position.
+///
+/// The keys are the indices of the elements, serialized as strings like
+/// [`serialize_stringify`] does,
+/// so the output is `{"0": v0, "1": v1, ...}` in every format.
+/// This is useful for consumers that expect sparse-array-like objects.
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "serde_iter::map::serialize_indexed")]
+///     bar: Vec<&'static str>,
+/// }
+///
+/// let foo = Foo {
+///     bar: vec!["x", "y"],
+/// };
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": {"0": "x", "1": "y"}
+/// }));
+/// ```
+pub fn serialize_indexed<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut map = serializer.serialize_map(Some(iter.size_hint().0))?;
+    for (index, value) in iter.enumerate() {
+        map.serialize_entry(&Stringified(&index), &value)?;
+    }
+    map.end()
+}
+
+/// Serializes an iterator of 2-tuples whose values are iterators into a serde map of sequences.
+///
+/// Each value is serialized as a sequence, which is useful for adjacency lists.
+/// Each inner iterator is cloned when its entry is serialized.
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Graph<'a> {
+///     #[serde(serialize_with = "serde_iter::map::serialize_seq_values")]
+///     edges: Vec<(&'static str, std::slice::Iter<'a, &'static str>)>,
+/// }
+///
+/// let from_a = ["b", "c"];
+/// let graph = Graph {
+///     edges: vec![("a", from_a.iter()), ("b", [].iter())],
+/// };
+/// assert_eq!(serde_json::to_value(&graph).unwrap(), serde_json::json!({
+///     "edges": {"a": ["b", "c"], "b": []}
+/// }));
+/// ```
+pub fn serialize_seq_values<S, T, K, Inner, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, Inner)> + Clone,
+    K: Serialize,
+    Inner: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut map = serializer.serialize_map(Some(iter.size_hint().0))?;
+    for (key, value) in iter {
+        map.serialize_entry(&key, &SeqValue(&value))?;
+    }
+    map.end()
+}
+
+struct SeqValue<'a, Inner>(&'a Inner);
+
+impl<'a, Inner, V> Serialize for SeqValue<'a, Inner>
+where
+    Inner: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let iter = self.0.clone().into_iter();
+        let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+        for value in iter {
+            seq.serialize_element(&value)?;
+        }
+        seq.end()
+    }
+}
+
+/// Serializes an iterator of 2-tuples into a serde map, failing on duplicate keys.
+///
+/// Unlike [`serialize`], which emits every entry and leaves duplicates to the format
+/// (usually resulting in the last value winning when deserialized),
+/// this function tracks the keys seen so far
+/// and returns a custom serialization error naming the first repeated key.
+///
+/// *This function requires the "std" feature to be enabled (enabled by default).*
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "serde_iter::map::serialize_unique")]
+///     bar: Vec<(&'static str, i32)>,
+/// }
+///
+/// let unique = Foo {
+///     bar: vec![("a", 1), ("b", 2)],
+/// };
+/// assert_eq!(serde_json::to_value(&unique).unwrap(), serde_json::json!({
+///     "bar": {"a": 1, "b": 2}
+/// }));
+///
+/// let duplicate = Foo {
+///     bar: vec![("a", 1), ("a", 2)],
+/// };
+/// let err = serde_json::to_value(&duplicate).unwrap_err();
+/// assert_eq!(err.to_string(), r#"duplicate key "a""#);
+/// ```
+#[cfg(feature = "std")]
+pub fn serialize_unique<S, T, K, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize + Hash + Eq + Debug,
+    V: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut map = serializer.serialize_map(Some(iter.size_hint().0))?;
+    let mut seen = HashSet::new();
+    for (key, value) in iter {
+        if seen.contains(&key) {
+            return Err(S::Error::custom(format_args!("duplicate key {:?}", key)));
+        }
+        map.serialize_entry(&key, &value)?;
+        seen.insert(key);
+    }
+    map.end()
+}
+
+/// Serializes an iterator of 2-tuples into a serde sequence of `[key, value]` pairs.
+///
+/// Each entry is serialized as a 2-element tuple,
+/// so the output is `[[k1, v1], [k2, v2]]` instead of a map.
+/// This avoids the limitation of formats like JSON that only accept string keys in maps,
+/// so keys of any type are preserved, as well as the order of the entries.
+/// This is also the encoding expected by e.g. the JavaScript `Map` constructor.
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "serde_iter::map::serialize_as_pairs")]
+///     bar: Vec<((u32, u32), &'static str)>,
+/// }
+///
+/// let foo = Foo {
+///     bar: vec![((0, 1), "a"), ((2, 3), "b")],
+/// };
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": [[[0, 1], "a"], [[2, 3], "b"]]
+/// }));
+/// ```
+pub fn serialize_as_pairs<S, T, K, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize,
+    V: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    for (key, value) in iter {
+        seq.serialize_element(&(key, value))?;
+    }
+    seq.end()
+}
+
 #[cfg(test)]
 mod tests {
-    use serde::Serialize;
+    use std::cell::Cell;
+    use std::collections::{BTreeMap, HashMap};
+
+    use serde::{Deserialize, Serialize};
     use serde_json::{json, to_value};
 
+    #[cfg(feature = "std")]
+    use super::NormalizeKeys;
+    use super::{AsMap, CappedWithCount, KeyedWithContext, LazyValues, TryKeyedBy, WithSummary};
+
     #[derive(Serialize)]
     struct Foo<T>
     where
@@ -84,7 +1133,7 @@ mod tests {
 
     #[test]
     fn test_vec_map() {
-        let vec = vec!["abcdef", "abcdefg"];
+        let vec = ["abcdef", "abcdefg"];
         let value = to_value(Foo {
             bar: vec.iter().map(|x| (*x, x.len())),
         });
@@ -99,4 +1148,626 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn test_lazy_values() {
+        let calls = Cell::new(0);
+        let lazy = LazyValues::new(vec!["abc", "de"], |key: &&str| {
+            calls.set(calls.get() + 1);
+            key.len()
+        });
+        assert_eq!(calls.get(), 0);
+
+        for expected_calls in &[2, 4] {
+            let value = to_value(&lazy).expect("Failed to serialize");
+            assert_eq!(value, json!({"abc": 3, "de": 2}));
+            assert_eq!(calls.get(), *expected_calls);
+        }
+    }
+
+    #[test]
+    fn test_capped_with_count() {
+        let entries = vec![("a", 1), ("b", 2), ("c", 3), ("d", 4), ("e", 5)];
+        let value = to_value(CappedWithCount::new(entries, 2));
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "a": 1,
+                "b": 2,
+                "_omitted": 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_capped_without_omission() {
+        let entries = vec![("a", 1), ("b", 2)];
+        let value = to_value(CappedWithCount::new(entries, 2));
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "a": 1,
+                "b": 2
+            })
+        );
+    }
+
+    fn parse_id(value: &&str) -> Result<u32, String> {
+        let id = value.split(':').next().unwrap_or_default();
+        id.parse().map_err(|_| format!("invalid id {:?}", id))
+    }
+
+    #[test]
+    fn test_try_keyed_by() {
+        let value = to_value(TryKeyedBy::new(vec!["1:foo", "2:bar"], parse_id));
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "1": "1:foo",
+                "2": "2:bar"
+            })
+        );
+    }
+
+    #[test]
+    fn test_try_keyed_by_failure() {
+        let value = to_value(TryKeyedBy::new(vec!["1:foo", "x:bar", "y:qux"], parse_id));
+        let err = value.expect_err("Invalid key should fail");
+        assert_eq!(err.to_string(), "invalid id \"x\"");
+    }
+
+    #[test]
+    fn test_with_summary() {
+        let entries = vec![("a", 1), ("b", 2), ("c", 3)];
+        let value = to_value(WithSummary::new(entries, "_total", |sum, value| {
+            sum + value
+        }));
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "a": 1,
+                "b": 2,
+                "c": 3,
+                "_total": 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_with_summary_empty() {
+        let entries: Vec<(&str, i32)> = vec![];
+        let value = to_value(WithSummary::new(entries, "_total", |sum, value| {
+            sum + value
+        }));
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({}));
+    }
+
+    #[test]
+    fn test_with_summary_bincode() {
+        let empty: Vec<(&str, i32)> = vec![];
+        let empty =
+            bincode::serialize(&WithSummary::new(empty, "_total", |sum, value| sum + value));
+        let empty = empty.expect("Failed to serialize");
+        let decoded_empty = bincode::deserialize::<BTreeMap<String, i32>>(&empty);
+        let decoded_empty = decoded_empty.expect("Failed to deserialize");
+        assert!(decoded_empty.is_empty());
+
+        let entries = vec![("a", 1), ("b", 2)];
+        let nonempty = bincode::serialize(&WithSummary::new(entries, "_total", |sum, value| {
+            sum + value
+        }));
+        let nonempty = nonempty.expect("Failed to serialize");
+        let decoded = bincode::deserialize::<BTreeMap<String, i32>>(&nonempty);
+        let decoded = decoded.expect("Failed to deserialize");
+        let expected: BTreeMap<String, i32> = vec![("a", 1), ("b", 2), ("_total", 3)]
+            .into_iter()
+            .map(|(key, value)| (key.to_owned(), value))
+            .collect();
+        assert_eq!(decoded, expected);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_normalize_keys() {
+        let entries = vec![("Foo", 1), ("foo", 2), ("BAR", 3)];
+        let value = to_value(NormalizeKeys::new(entries, |key: &&str| key.to_lowercase()));
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "foo": 1,
+                "bar": 3
+            })
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_normalize_keys_strict() {
+        let entries = vec![("Foo", 1), ("foo", 2)];
+        let value = to_value(NormalizeKeys::strict(entries, |key: &&str| {
+            key.to_lowercase()
+        }));
+        let err = value.expect_err("Colliding keys should fail");
+        assert_eq!(err.to_string(), "duplicate normalized key \"foo\"");
+    }
+
+    #[test]
+    fn test_keyed_with_context() {
+        struct Symbols(Vec<&'static str>);
+
+        let symbols = Symbols(vec!["main", "init", "exit"]);
+        let keyed =
+            KeyedWithContext::new([2_usize, 0], &symbols, |symbols: &Symbols, id: &usize| {
+                symbols.0.get(*id).copied().unwrap_or("<unknown>")
+            });
+        let value = to_value(keyed.clone());
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "exit": 2,
+                "main": 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_btree_map_ref() {
+        #[derive(Serialize)]
+        struct Bar<'a> {
+            #[serde(with = "super")]
+            bar: &'a BTreeMap<&'static str, i32>,
+        }
+
+        let mut map = BTreeMap::new();
+        map.insert("b", 2);
+        map.insert("a", 1);
+        let value = to_value(Bar { bar: &map });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"a": 1, "b": 2}
+            })
+        );
+    }
+
+    #[test]
+    fn test_hash_map_ref() {
+        #[derive(Serialize)]
+        struct Bar<'a> {
+            #[serde(with = "super")]
+            bar: &'a HashMap<String, i32>,
+        }
+
+        let mut map = HashMap::new();
+        map.insert("a".to_owned(), 1);
+        map.insert("b".to_owned(), 2);
+        let bar = Bar { bar: &map };
+        let first = to_value(&bar).expect("Failed to serialize");
+        let second = to_value(&bar).expect("Failed to serialize");
+        assert_eq!(first, json!({"bar": {"a": 1, "b": 2}}));
+        assert_eq!(second, first);
+    }
+
+    #[test]
+    fn test_hash_map_iter() {
+        #[derive(Serialize)]
+        struct Bar<'a> {
+            #[serde(with = "super")]
+            bar: std::collections::hash_map::Iter<'a, String, i32>,
+        }
+
+        let mut map = HashMap::new();
+        map.insert("a".to_owned(), 1);
+        map.insert("b".to_owned(), 2);
+        let bar = Bar { bar: map.iter() };
+        let first = to_value(&bar).expect("Failed to serialize");
+        let second = to_value(&bar).expect("Failed to serialize");
+        assert_eq!(first, json!({"bar": {"a": 1, "b": 2}}));
+        assert_eq!(second, first);
+    }
+
+    #[test]
+    fn test_vec_of_pairs() {
+        let value = to_value(Foo {
+            bar: vec![("a", 1), ("b", 2)],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"a": 1, "b": 2}
+            })
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_serialize_sorted() {
+        #[derive(Serialize)]
+        struct Sorted<T>
+        where
+            T: IntoIterator<Item = (&'static str, i32)> + Clone,
+        {
+            #[serde(serialize_with = "super::serialize_sorted")]
+            bar: T,
+        }
+
+        let map: HashMap<&'static str, i32> = vec![("d", 4), ("b", 2), ("a", 1), ("c", 3)]
+            .into_iter()
+            .collect();
+        let json = serde_json::to_string(&Sorted { bar: map });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(json, r#"{"bar":{"a":1,"b":2,"c":3,"d":4}}"#);
+
+        let duplicates = serde_json::to_string(&Sorted {
+            bar: vec![("b", 1), ("a", 2), ("b", 3), ("a", 4), ("b", 5)],
+        });
+        let duplicates = duplicates.expect("Failed to serialize");
+        assert_eq!(duplicates, r#"{"bar":{"a":4,"b":5}}"#);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Hashed {
+        #[serde(with = "super")]
+        bar: HashMap<String, i32>,
+    }
+
+    #[cfg(feature = "alloc")]
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Ordered {
+        #[serde(with = "super")]
+        bar: BTreeMap<String, i32>,
+    }
+
+    #[cfg(feature = "alloc")]
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Pairs {
+        #[serde(with = "super")]
+        bar: Vec<(String, i32)>,
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_round_trip_hash_map() {
+        let mut map = HashMap::new();
+        map.insert("a".to_owned(), 1);
+        map.insert("b".to_owned(), 2);
+        let value = to_value(Hashed { bar: map.clone() });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": {"a": 1, "b": 2}}));
+
+        let hashed: Hashed = serde_json::from_value(value).expect("Failed to deserialize");
+        assert_eq!(hashed, Hashed { bar: map });
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_round_trip_btree_map() {
+        let mut map = BTreeMap::new();
+        map.insert("a".to_owned(), 1);
+        let value = to_value(Ordered { bar: map.clone() });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": {"a": 1}}));
+
+        let ordered: Ordered = serde_json::from_value(value).expect("Failed to deserialize");
+        assert_eq!(ordered, Ordered { bar: map });
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_deserialize_empty_and_duplicates() {
+        let empty: Ordered = serde_json::from_str(r#"{"bar":{}}"#).expect("Failed to deserialize");
+        assert!(empty.bar.is_empty());
+
+        let hashed: Hashed =
+            serde_json::from_str(r#"{"bar":{"a":1,"a":2}}"#).expect("Failed to deserialize");
+        assert_eq!(hashed.bar.get("a"), Some(&2));
+
+        let pairs: Pairs =
+            serde_json::from_str(r#"{"bar":{"a":1,"a":2}}"#).expect("Failed to deserialize");
+        assert_eq!(pairs.bar, vec![("a".to_owned(), 1), ("a".to_owned(), 2)]);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_deserialize_untrusted_length() {
+        // The length prefix claims far more entries than could ever be allocated.
+        let input = 0x0fff_ffff_ffff_ffff_u64.to_le_bytes();
+        assert!(bincode::deserialize::<Pairs>(&input).is_err());
+    }
+
+    #[test]
+    fn test_serialize_stringify() {
+        #[derive(Serialize)]
+        struct Stringify {
+            #[serde(serialize_with = "super::serialize_stringify")]
+            bar: Vec<(u32, &'static str)>,
+        }
+
+        let value = to_value(Stringify {
+            bar: vec![(1, "a"), (20, "b")],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": {"1": "a", "20": "b"}}));
+    }
+
+    #[test]
+    fn test_serialize_filtered() {
+        #[derive(Serialize)]
+        struct Odd {
+            #[serde(serialize_with = "odd")]
+            bar: Vec<(&'static str, u32)>,
+        }
+
+        fn odd<S: serde::Serializer>(
+            bar: &Vec<(&'static str, u32)>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            super::serialize_filtered(bar, |_: &&str, value: &u32| value % 2 == 1, serializer)
+        }
+
+        let value = to_value(Odd {
+            bar: vec![("a", 1), ("b", 2), ("c", 3), ("d", 4)],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": {"a": 1, "c": 3}}));
+        let bar = value.get("bar").and_then(|bar| bar.as_object());
+        assert!(bar.is_some_and(|bar| !bar.contains_key("b") && !bar.contains_key("d")));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_serialize_grouped() {
+        #[derive(Serialize)]
+        struct Grouped {
+            #[serde(serialize_with = "super::serialize_grouped")]
+            bar: Vec<(&'static str, i32)>,
+        }
+
+        let value = to_value(Grouped {
+            bar: vec![("a", 1), ("a", 2), ("b", 3)],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": {"a": [1, 2], "b": [3]}}));
+
+        let json = serde_json::to_string(&Grouped {
+            bar: vec![("b", 1), ("a", 2), ("b", 3), ("c", 4)],
+        });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(json, r#"{"bar":{"b":[1,3],"a":[2],"c":[4]}}"#);
+    }
+
+    #[derive(Serialize)]
+    struct NullIfEmpty<T>
+    where
+        T: IntoIterator<Item = (&'static str, i32)> + Clone,
+    {
+        #[serde(serialize_with = "super::serialize_null_if_empty")]
+        bar: T,
+    }
+
+    #[test]
+    fn test_serialize_null_if_empty() {
+        let value = to_value(NullIfEmpty { bar: vec![] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": null}));
+    }
+
+    #[test]
+    fn test_serialize_null_if_not_empty() {
+        let calls = Cell::new(0);
+        let value = to_value(NullIfEmpty {
+            bar: [("a", 1), ("b", 2)].iter().map(|&entry| {
+                calls.set(calls.get() + 1);
+                entry
+            }),
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": {"a": 1, "b": 2}}));
+        assert_eq!(calls.get(), 2);
+    }
+
+    struct Zipped<KI, VI>(KI, VI);
+
+    impl<KI, VI> Serialize for Zipped<KI, VI>
+    where
+        KI: IntoIterator<Item = &'static str> + Clone,
+        VI: IntoIterator<Item = i32> + Clone,
+    {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize_zip(&self.0, &self.1, serializer)
+        }
+    }
+
+    #[test]
+    fn test_serialize_zip() {
+        let value = to_value(Zipped(vec!["a", "b"], vec![1, 2]));
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"a": 1, "b": 2}));
+    }
+
+    #[test]
+    fn test_serialize_zip_uneven() {
+        let short_values = to_value(Zipped(vec!["a", "b", "c"], vec![1]));
+        let short_values = short_values.expect("Failed to serialize");
+        assert_eq!(short_values, json!({"a": 1}));
+
+        let short_keys = to_value(Zipped(vec!["a"], 1..));
+        let short_keys = short_keys.expect("Failed to serialize");
+        assert_eq!(short_keys, json!({"a": 1}));
+    }
+
+    #[test]
+    fn test_serialize_indexed() {
+        #[derive(Serialize)]
+        struct Indexed<T: IntoIterator<Item = &'static str> + Clone> {
+            #[serde(serialize_with = "super::serialize_indexed")]
+            bar: T,
+        }
+
+        let value = to_value(Indexed {
+            bar: vec!["x", "y"],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": {"0": "x", "1": "y"}}));
+
+        let empty = to_value(Indexed { bar: vec![] });
+        let empty = empty.expect("Failed to serialize");
+        assert_eq!(empty, json!({"bar": {}}));
+    }
+
+    #[test]
+    fn test_serialize_seq_values() {
+        #[derive(Serialize)]
+        struct Adjacency {
+            #[serde(serialize_with = "super::serialize_seq_values")]
+            bar: Vec<(&'static str, Vec<i32>)>,
+        }
+
+        let value = to_value(Adjacency {
+            bar: vec![("a", vec![1, 2]), ("b", vec![3])],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": {"a": [1, 2], "b": [3]}}));
+    }
+
+    #[cfg(feature = "std")]
+    #[derive(Serialize)]
+    struct Unique {
+        #[serde(serialize_with = "super::serialize_unique")]
+        bar: Vec<(u32, &'static str)>,
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_serialize_unique() {
+        let value = to_value(Unique {
+            bar: vec![(1, "a"), (2, "b")],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": {"1": "a", "2": "b"}}));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_serialize_unique_duplicate() {
+        let err = to_value(Unique {
+            bar: vec![(1, "a"), (2, "b"), (1, "c")],
+        });
+        let err = err.expect_err("Serialized a duplicate key");
+        assert_eq!(err.to_string(), "duplicate key 1");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_serialize_unique_owned_keys() {
+        /// A key type that is not `Clone`.
+        #[derive(Serialize, PartialEq, Eq, Hash, Debug)]
+        struct Key(u32);
+
+        let entries = (1..3).map(|i| (Key(i), i * 10));
+        let value = super::serialize_unique(&entries, serde_json::value::Serializer);
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"1": 10, "2": 20}));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_deserialize_into_accumulates() {
+        let mut target = HashMap::new();
+        let first = super::deserialize_into(&mut target, json!({"a": 1, "b": 2}));
+        first.expect("Failed to deserialize");
+        let second = super::deserialize_into(&mut target, json!({"b": 3, "c": 4}));
+        second.expect("Failed to deserialize");
+
+        let expected: HashMap<String, i32> = vec![("a", 1), ("b", 3), ("c", 4)]
+            .into_iter()
+            .map(|(key, value)| (key.to_owned(), value))
+            .collect();
+        assert_eq!(target, expected);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_deserialize_into_cleared() {
+        let mut target: HashMap<String, i32> = HashMap::new();
+        let first = super::deserialize_into(&mut target, json!({"a": 1, "b": 2}));
+        first.expect("Failed to deserialize");
+        let capacity = target.capacity();
+
+        target.clear();
+        let second = super::deserialize_into(&mut target, json!({"c": 3}));
+        second.expect("Failed to deserialize");
+        assert_eq!(target.len(), 1);
+        assert_eq!(target.get("c"), Some(&3));
+        assert_eq!(target.capacity(), capacity);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_deserialize_into_untrusted_length() {
+        use bincode::Options;
+
+        // The length prefix claims far more entries than could ever be allocated.
+        let input = 0x0fff_ffff_ffff_ffff_u64.to_le_bytes();
+        let options = bincode::DefaultOptions::new().with_fixint_encoding();
+        let mut deserializer = bincode::Deserializer::from_slice(&input, options);
+        let mut target: HashMap<String, i32> = HashMap::new();
+        assert!(super::deserialize_into(&mut target, &mut deserializer).is_err());
+        assert!(target.is_empty());
+    }
+
+    #[test]
+    fn test_as_map() {
+        let top_level = to_value(AsMap((1..3).map(|x| (x.to_string(), x * 10))));
+        let top_level = top_level.expect("Failed to serialize");
+        assert_eq!(top_level, json!({"1": 10, "2": 20}));
+
+        #[derive(Serialize)]
+        struct Field {
+            name: &'static str,
+            counts: AsMap<Vec<(&'static str, u32)>>,
+        }
+
+        let field = to_value(Field {
+            name: "foo",
+            counts: AsMap(vec![("a", 1), ("b", 2)]),
+        });
+        let field = field.expect("Failed to serialize");
+        assert_eq!(field, json!({"name": "foo", "counts": {"a": 1, "b": 2}}));
+    }
+
+    #[test]
+    fn test_serialize_as_pairs() {
+        #[derive(Serialize)]
+        struct Pairs<K, V>
+        where
+            K: Serialize + Clone,
+            V: Serialize + Clone,
+        {
+            #[serde(serialize_with = "super::serialize_as_pairs")]
+            bar: Vec<(K, V)>,
+        }
+
+        let strings = to_value(Pairs {
+            bar: vec![("a", 1), ("b", 2)],
+        });
+        let strings = strings.expect("Failed to serialize");
+        assert_eq!(strings, json!({"bar": [["a", 1], ["b", 2]]}));
+
+        let numbers = to_value(Pairs {
+            bar: vec![(3, "c"), (1, "a"), (3, "d")],
+        });
+        let numbers = numbers.expect("Failed to serialize");
+        assert_eq!(numbers, json!({"bar": [[3, "c"], [1, "a"], [3, "d"]]}));
+    }
 }
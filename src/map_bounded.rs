@@ -0,0 +1,138 @@
+//! Serializes an iterator of serializable 2-tuples into a serde map,
+//! failing cleanly if the output would exceed a size budget.
+//!
+//! Before anything is emitted, each entry is encoded as compact JSON to measure its size,
+//! and the total size of the map as compact JSON is compared with the budget in bytes.
+//! If the budget would be exceeded, serialization fails with the error `"response too large"`
+//! instead of emitting a truncated or invalid document.
+//! The size is measured in compact JSON regardless of the actual serializer.
+//!
+//! The iterator is cloned twice: once to measure the entries and once to serialize them.
+//!
+//! *This module requires the "map_bounded" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! let mut buf = Vec::new();
+//! serde_iter::map_bounded::serialize(
+//!     &vec![("a", 1)],
+//!     16,
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), r#"{"a":1}"#);
+//!
+//! let mut buf = Vec::new();
+//! let err = serde_iter::map_bounded::serialize(
+//!     &vec![("a", "a long value exceeding the budget")],
+//!     16,
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap_err();
+//! assert_eq!(err.to_string(), "response too large");
+//! ```
+
+use serde::ser::{Error, Serialize, SerializeMap, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+///
+/// Returns an error if the map would be larger than `budget` bytes of compact JSON,
+/// or if an entry cannot be encoded as JSON.
+pub fn serialize<S, T, K, V>(iter: &T, budget: usize, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize,
+    V: Serialize,
+{
+    // The opening and closing braces.
+    let mut size: usize = 2;
+    for (index, (key, value)) in iter.clone().into_iter().enumerate() {
+        let entry = serde_json::to_vec(&Entry(&key, &value)).map_err(S::Error::custom)?;
+        // The entry without its braces, preceded by a comma unless it is the first entry.
+        let entry_size = entry.len().saturating_sub(2);
+        let separator = usize::from(index > 0);
+        size = size.saturating_add(entry_size).saturating_add(separator);
+        if size > budget {
+            return Err(S::Error::custom("response too large"));
+        }
+    }
+
+    let iter = iter.clone().into_iter();
+    let mut map = serializer.serialize_map(Some(iter.size_hint().0))?;
+    for (key, value) in iter {
+        map.serialize_entry(&key, &value)?;
+    }
+    map.end()
+}
+
+/// A single entry serialized as a map, to measure the size of the entry as JSON.
+struct Entry<'a, K, V>(&'a K, &'a V);
+
+impl<'a, K, V> Serialize for Entry<'a, K, V>
+where
+    K: Serialize,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(self.0, self.1)?;
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_string, to_value};
+
+    type Entries = Vec<(u32, &'static str)>;
+
+    fn max_32<S: Serializer>(iter: &Entries, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, 32, serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(serialize_with = "max_32")]
+        bar: Entries,
+    }
+
+    #[test]
+    fn test_within_budget() {
+        let value = to_value(Foo {
+            bar: vec![(1, "a"), (2, "b")],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"1": "a", "2": "b"}
+            })
+        );
+    }
+
+    #[test]
+    fn test_exact_budget() {
+        let bar: Entries = vec![(1, "abcdefghij"), (2, "klmnopq")];
+        let inner = to_string(
+            &bar.iter()
+                .copied()
+                .collect::<std::collections::BTreeMap<_, _>>(),
+        );
+        assert_eq!(inner.expect("Failed to serialize").len(), 32);
+        assert!(to_value(Foo { bar }).is_ok());
+    }
+
+    #[test]
+    fn test_too_large() {
+        let err = to_value(Foo {
+            bar: (0..10).map(|key| (key, "value")).collect(),
+        });
+        let err = err.expect_err("Expected a size error");
+        assert_eq!(err.to_string(), "response too large");
+    }
+}
@@ -0,0 +1,109 @@
+//! Serializes an iterator of serializable 2-tuples into a serde map keyed by value hash.
+//!
+//! Each value is encoded as JSON and hashed with SHA-256,
+//! and the output is a map from the lowercase hex digest of each distinct value
+//! to the first key that maps to it, i.e. `{hash(value): key, ...}`.
+//! This is useful for building indexes from values to canonical keys.
+//!
+//! Entries with identical values have the same hash,
+//! so only the first of them is emitted.
+//!
+//! *This module requires the "map_by_value_hash" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::map_by_value_hash")]
+//!     bar: Vec<(&'static str, &'static str)>,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: vec![("a", "qux"), ("b", "qux")],
+//! };
+//! assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+//!     "bar": {
+//!         "74a10314c221f3fdaf1cf9ef56e562dd1a02f4abfe9cea68c1f4a974bb3ef13f": "a"
+//!     }
+//! }));
+//! ```
+
+use std::collections::HashSet;
+
+use serde::ser::{Error, Serialize, SerializeMap, Serializer};
+
+use crate::digest;
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, K, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize,
+    V: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut map = serializer.serialize_map(None)?;
+    let mut seen = HashSet::new();
+    for (key, value) in iter {
+        let hash = digest::content_digest(b"", &value).map_err(S::Error::custom)?;
+        if seen.insert(hash) {
+            map.serialize_entry(&digest::to_hex(&hash), &key)?;
+        }
+    }
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde_json::{json, to_value, Value};
+
+    #[derive(Serialize)]
+    struct Foo<T>
+    where
+        T: IntoIterator<Item = (&'static str, i32)> + Clone,
+    {
+        #[serde(with = "super")]
+        bar: T,
+    }
+
+    fn entries(value: &Value) -> Vec<&Value> {
+        match value.get("bar") {
+            Some(Value::Object(map)) => map.values().collect(),
+            _ => panic!("Expected an object"),
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo { bar: vec![] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {}
+            })
+        );
+    }
+
+    #[test]
+    fn test_collapse() {
+        let value = to_value(Foo {
+            bar: vec![("a", 1), ("b", 1)],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(entries(&value), vec![&json!("a")]);
+    }
+
+    #[test]
+    fn test_distinct() {
+        let value = to_value(Foo {
+            bar: vec![("a", 1), ("b", 2), ("c", 1)],
+        });
+        let value = value.expect("Failed to serialize");
+        let mut keys = entries(&value);
+        keys.sort_by_key(|key| key.as_str());
+        assert_eq!(keys, vec![&json!("a"), &json!("b")]);
+    }
+}
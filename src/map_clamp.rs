@@ -0,0 +1,214 @@
+//! Serializes an iterator of serializable 2-tuples into a serde map,
+//! enforcing that values are within a range.
+//!
+//! Values below `min` or above `max` are either clamped to the nearest bound,
+//! or cause the serialization to fail, as selected by [`OutOfRange`](enum.OutOfRange.html).
+//! Values that are not comparable with the bounds (e.g. `NaN`) always cause the serialization
+//! to fail, as does a `min` that is greater than, or not comparable with, `max`.
+//!
+//! *This module requires the "map_clamp" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! use serde_iter::map_clamp::OutOfRange;
+//!
+//! let mut buf = Vec::new();
+//! serde_iter::map_clamp::serialize(
+//!     &vec![("a", 500), ("b", 50)],
+//!     0,
+//!     100,
+//!     OutOfRange::Clamp,
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), r#"{"a":100,"b":50}"#);
+//! ```
+
+use std::cmp::Ordering;
+
+use serde::ser::{Error, Serialize, SerializeMap, Serializer};
+
+/// The behaviour when a value is out of range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutOfRange {
+    /// Replaces the value with the nearest bound.
+    Clamp,
+    /// Fails the serialization.
+    Error,
+}
+
+/// Refer to the [module-level documentation](index.html).
+///
+/// Returns an error if `min` is not less than or equal to `max`,
+/// or if a value is not comparable with the bounds.
+pub fn serialize<S, T, K, V>(
+    iter: &T,
+    min: V,
+    max: V,
+    out_of_range: OutOfRange,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize,
+    V: Serialize + PartialOrd,
+{
+    if !matches!(
+        min.partial_cmp(&max),
+        Some(Ordering::Less | Ordering::Equal)
+    ) {
+        return Err(S::Error::custom("map_clamp bounds must satisfy min <= max"));
+    }
+
+    let iter = iter.clone().into_iter();
+    let mut map = serializer.serialize_map(Some(iter.size_hint().0))?;
+    for (position, (key, value)) in iter.enumerate() {
+        let bound = match (value.partial_cmp(&min), value.partial_cmp(&max)) {
+            (Some(Ordering::Less), _) => Some(&min),
+            (_, Some(Ordering::Greater)) => Some(&max),
+            (Some(_), Some(_)) => None,
+            (None, _) | (_, None) => {
+                return Err(S::Error::custom(format_args!(
+                    "map value at position {} is not comparable with the bounds",
+                    position
+                )));
+            }
+        };
+        match (bound, out_of_range) {
+            (None, _) => map.serialize_entry(&key, &value)?,
+            (Some(bound), OutOfRange::Clamp) => map.serialize_entry(&key, bound)?,
+            (Some(_), OutOfRange::Error) => {
+                return Err(S::Error::custom(format_args!(
+                    "map value at position {} is out of range",
+                    position
+                )));
+            }
+        }
+    }
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_value};
+
+    use super::OutOfRange;
+
+    type Entries = [(&'static str, i32); 3];
+
+    fn clamp<S: Serializer>(iter: &Entries, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, 0, 100, OutOfRange::Clamp, serializer)
+    }
+
+    fn strict<S: Serializer>(iter: &Entries, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, 0, 100, OutOfRange::Error, serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Clamped {
+        #[serde(serialize_with = "clamp")]
+        bar: Entries,
+    }
+
+    #[derive(Serialize)]
+    struct Strict {
+        #[serde(serialize_with = "strict")]
+        bar: Entries,
+    }
+
+    #[test]
+    fn test_clamp() {
+        let value = to_value(Clamped {
+            bar: [("a", 500), ("b", -5), ("c", 50)],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"a": 100, "b": 0, "c": 50}
+            })
+        );
+    }
+
+    #[test]
+    fn test_error() {
+        let result = to_value(Strict {
+            bar: [("a", 500), ("b", -5), ("c", 50)],
+        });
+        let error = result.expect_err("Expected an out-of-range error");
+        assert_eq!(error.to_string(), "map value at position 0 is out of range");
+    }
+
+    #[test]
+    fn test_in_range() {
+        let value = to_value(Strict {
+            bar: [("a", 0), ("b", 100), ("c", 50)],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"a": 0, "b": 100, "c": 50}
+            })
+        );
+    }
+
+    #[test]
+    fn test_reversed_bounds() {
+        fn reversed<S: Serializer>(iter: &Entries, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize(iter, 100, 0, OutOfRange::Clamp, serializer)
+        }
+
+        #[derive(Serialize)]
+        struct Reversed {
+            #[serde(serialize_with = "reversed")]
+            bar: Entries,
+        }
+
+        let result = to_value(Reversed {
+            bar: [("a", 0), ("b", 100), ("c", 50)],
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_not_comparable() {
+        type Floats = [(&'static str, f64); 2];
+
+        fn floats<S: Serializer>(iter: &Floats, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize(iter, 0.0, 1.0, OutOfRange::Clamp, serializer)
+        }
+
+        fn nan_bound<S: Serializer>(iter: &Floats, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize(iter, f64::NAN, 1.0, OutOfRange::Clamp, serializer)
+        }
+
+        #[derive(Serialize)]
+        struct Float {
+            #[serde(serialize_with = "floats")]
+            bar: Floats,
+        }
+
+        #[derive(Serialize)]
+        struct NanBound {
+            #[serde(serialize_with = "nan_bound")]
+            bar: Floats,
+        }
+
+        let result = to_value(Float {
+            bar: [("a", 0.5), ("b", f64::NAN)],
+        });
+        let error = result.expect_err("Expected a comparison error");
+        assert_eq!(
+            error.to_string(),
+            "map value at position 1 is not comparable with the bounds"
+        );
+
+        let nan_bound = to_value(NanBound {
+            bar: [("a", 0.5), ("b", 0.5)],
+        });
+        assert!(nan_bound.is_err());
+    }
+}
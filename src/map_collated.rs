@@ -0,0 +1,125 @@
+//! Serializes an iterator of serializable 2-tuples into a serde map,
+//! ordering string keys with a locale-aware collator.
+//!
+//! The entries are collected and sorted by key with the Unicode Collation Algorithm,
+//! as tailored by the locale, before being serialized.
+//! This matters for user-facing sorted output in languages with non-ASCII letters;
+//! for example, `"ä"` sorts right after `"a"` in German but after `"z"` in Swedish.
+//! The relative order of entries with equal keys is preserved.
+//!
+//! The `serialize` function uses the root locale,
+//! which gives a sensible language-neutral order.
+//! Use [`serialize_locale`](fn.serialize_locale.html) to select a locale.
+//!
+//! *This module requires the "collation" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! let mut buf = Vec::new();
+//! serde_iter::map_collated::serialize_locale(
+//!     &vec![("z", 1), ("ä", 2), ("a", 3)],
+//!     "sv",
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), r#"{"a":3,"z":1,"ä":2}"#);
+//! ```
+
+use icu_collator::options::CollatorOptions;
+use icu_collator::Collator;
+use icu_locale_core::Locale;
+use serde::ser::{Error, Serialize, SerializeMap, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, K, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize + AsRef<str>,
+    V: Serialize,
+{
+    serialize_locale(iter, "und", serializer)
+}
+
+/// Orders keys with the collation of `locale`, a BCP 47 language tag such as `"de"` or `"sv"`.
+///
+/// Returns an error if the language tag is invalid.
+pub fn serialize_locale<S, T, K, V>(
+    iter: &T,
+    locale: &str,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize + AsRef<str>,
+    V: Serialize,
+{
+    let locale: Locale = locale.parse().map_err(S::Error::custom)?;
+    let collator =
+        Collator::try_new(locale.into(), CollatorOptions::default()).map_err(S::Error::custom)?;
+
+    let mut entries: Vec<(K, V)> = iter.clone().into_iter().collect();
+    entries.sort_by(|(a, _), (b, _)| collator.compare(a.as_ref(), b.as_ref()));
+
+    let mut map = serializer.serialize_map(Some(entries.len()))?;
+    for (key, value) in &entries {
+        map.serialize_entry(key, value)?;
+    }
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+    use serde_json::to_string;
+
+    type Entries = [(&'static str, i32); 5];
+
+    const ENTRIES: Entries = [("z", 1), ("ä", 2), ("a", 3), ("Ö", 4), ("o", 5)];
+
+    fn swedish<S: Serializer>(iter: &Entries, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize_locale(iter, "sv", serializer)
+    }
+
+    fn invalid<S: Serializer>(iter: &Entries, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize_locale(iter, "not a locale", serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Root {
+        #[serde(with = "super")]
+        bar: Entries,
+    }
+
+    #[derive(Serialize)]
+    struct Swedish {
+        #[serde(serialize_with = "swedish")]
+        bar: Entries,
+    }
+
+    #[derive(Serialize)]
+    struct Invalid {
+        #[serde(serialize_with = "invalid")]
+        bar: Entries,
+    }
+
+    #[test]
+    fn test_root() {
+        let json = to_string(&Root { bar: ENTRIES });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(json, r#"{"bar":{"a":3,"ä":2,"o":5,"Ö":4,"z":1}}"#);
+    }
+
+    #[test]
+    fn test_swedish() {
+        let json = to_string(&Swedish { bar: ENTRIES });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(json, r#"{"bar":{"a":3,"o":5,"z":1,"ä":2,"Ö":4}}"#);
+    }
+
+    #[test]
+    fn test_invalid_locale() {
+        assert!(to_string(&Invalid { bar: ENTRIES }).is_err());
+    }
+}
@@ -0,0 +1,171 @@
+//! Serializes an iterator of serializable 2-tuples into a diff against a baseline map.
+//!
+//! The output is `{"changed": {...}, "removed": [...]}`,
+//! where `changed` contains the entries that are absent from the baseline
+//! or have a different value in it,
+//! and `removed` contains the keys of the baseline that are absent from the iterator.
+//! This produces compact patch documents for change tracking.
+//!
+//! Changed entries are emitted in iterator order,
+//! while removed keys are emitted in the iteration order of the baseline `HashMap`.
+//! The iterator is cloned twice: once to find the removed keys and once to serialize the changes.
+//!
+//! *This module requires the "map_diff" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! use std::collections::HashMap;
+//!
+//! let mut baseline = HashMap::new();
+//! baseline.insert("a", 1);
+//! baseline.insert("b", 2);
+//!
+//! let mut buf = Vec::new();
+//! serde_iter::map_diff::serialize(
+//!     &vec![("a", 1), ("c", 3)],
+//!     &baseline,
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(
+//!     String::from_utf8(buf).unwrap(),
+//!     r#"{"changed":{"c":3},"removed":["b"]}"#,
+//! );
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hash};
+
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, SerializeStruct, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, K, V, B>(
+    iter: &T,
+    baseline: &HashMap<K, V, B>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize + Eq + Hash,
+    V: Serialize + PartialEq,
+    B: BuildHasher,
+{
+    let mut present = HashSet::new();
+    for (key, _) in iter.clone() {
+        if let Some((baseline_key, _)) = baseline.get_key_value(&key) {
+            present.insert(baseline_key);
+        }
+    }
+
+    let mut st = serializer.serialize_struct("MapDiff", 2)?;
+    st.serialize_field("changed", &Changed { iter, baseline })?;
+    st.serialize_field("removed", &Removed { baseline, present })?;
+    st.end()
+}
+
+struct Changed<'a, T, K, V, B> {
+    iter: &'a T,
+    baseline: &'a HashMap<K, V, B>,
+}
+
+impl<'a, T, K, V, B> Serialize for Changed<'a, T, K, V, B>
+where
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize + Eq + Hash,
+    V: Serialize + PartialEq,
+    B: BuildHasher,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        for (key, value) in self.iter.clone() {
+            if self.baseline.get(&key) != Some(&value) {
+                map.serialize_entry(&key, &value)?;
+            }
+        }
+        map.end()
+    }
+}
+
+struct Removed<'a, K, V, B> {
+    baseline: &'a HashMap<K, V, B>,
+    present: HashSet<&'a K>,
+}
+
+impl<'a, K, V, B> Serialize for Removed<'a, K, V, B>
+where
+    K: Serialize + Eq + Hash,
+    B: BuildHasher,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer
+            .serialize_seq(Some(self.baseline.len().saturating_sub(self.present.len())))?;
+        for key in self.baseline.keys() {
+            if !self.present.contains(key) {
+                seq.serialize_element(key)?;
+            }
+        }
+        seq.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_value};
+
+    type Entries = [(&'static str, i32); 3];
+
+    fn against_baseline<S: Serializer>(iter: &Entries, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut baseline = HashMap::new();
+        baseline.insert("kept", 1);
+        baseline.insert("changed", 2);
+        baseline.insert("removed", 3);
+        super::serialize(iter, &baseline, serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(serialize_with = "against_baseline")]
+        bar: Entries,
+    }
+
+    #[test]
+    fn test_diff() {
+        let value = to_value(Foo {
+            bar: [("kept", 1), ("changed", 20), ("added", 4)],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {
+                    "changed": {"changed": 20, "added": 4},
+                    "removed": ["removed"]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_unchanged() {
+        let value = to_value(Foo {
+            bar: [("kept", 1), ("changed", 2), ("removed", 3)],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"changed": {}, "removed": []}
+            })
+        );
+    }
+}
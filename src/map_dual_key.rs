@@ -0,0 +1,80 @@
+//! Serializes an iterator of serializable 2-tuples into a serde map,
+//! emitting each value under both its original key and a transformed key.
+//!
+//! This is useful during schema migrations,
+//! where consumers of both the old and the new key names must be supported temporarily.
+//! The output is `{old: value, new: value, ...}`,
+//! with the original key of each entry immediately followed by its transformed key.
+//! Each value is therefore serialized twice.
+//!
+//! *This module requires the "map_dual_key" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! let mut buf = Vec::new();
+//! serde_iter::map_dual_key::serialize(
+//!     &vec![("uid", 42)],
+//!     |_: &&str| "user_id",
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), r#"{"uid":42,"user_id":42}"#);
+//! ```
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, K, V, F, K2>(iter: &T, key_fn: F, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize,
+    V: Serialize,
+    F: Fn(&K) -> K2,
+    K2: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut map = serializer.serialize_map(Some(iter.size_hint().0.saturating_mul(2)))?;
+    for (key, value) in iter {
+        map.serialize_entry(&key, &value)?;
+        map.serialize_entry(&key_fn(&key), &value)?;
+    }
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+    use serde_json::to_string;
+
+    type Entries = [(&'static str, u32); 2];
+
+    fn renamed<S: Serializer>(iter: &Entries, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(
+            iter,
+            |key: &&str| match *key {
+                "uid" => "user_id".to_owned(),
+                key => format!("new_{}", key),
+            },
+            serializer,
+        )
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(serialize_with = "renamed")]
+        bar: Entries,
+    }
+
+    #[test]
+    fn test_dual_key() {
+        let json = to_string(&Foo {
+            bar: [("uid", 1), ("age", 30)],
+        });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(
+            json,
+            r#"{"bar":{"uid":1,"user_id":1,"age":30,"new_age":30}}"#
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//! Serializes an iterator of `(key, value, expiry)` triples into a serde map,
+//! dropping the entries that have expired.
+//!
+//! An entry is emitted only if its expiry is strictly after the supplied `now`.
+//! This is useful for dumping caches without leaking stale entries.
+//! Passing `now` explicitly, e.g. `SystemTime::now()`, keeps the output reproducible in tests.
+//!
+//! *This module requires the "map_expiring" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! use std::time::{Duration, UNIX_EPOCH};
+//!
+//! let now = UNIX_EPOCH + Duration::from_secs(100);
+//!
+//! let mut buf = Vec::new();
+//! serde_iter::map_expiring::serialize(
+//!     &vec![
+//!         ("fresh", 1, UNIX_EPOCH + Duration::from_secs(200)),
+//!         ("stale", 2, UNIX_EPOCH + Duration::from_secs(50)),
+//!     ],
+//!     now,
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), r#"{"fresh":1}"#);
+//! ```
+
+use std::time::SystemTime;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, K, V>(iter: &T, now: SystemTime, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V, SystemTime)> + Clone,
+    K: Serialize,
+    V: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut map = serializer.serialize_map(None)?;
+    for (key, value, expiry) in iter {
+        if expiry > now {
+            map.serialize_entry(&key, &value)?;
+        }
+    }
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_value};
+
+    type Entries = [(&'static str, i32, SystemTime); 3];
+
+    fn at_100<S: Serializer>(iter: &Entries, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, UNIX_EPOCH + Duration::from_secs(100), serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(serialize_with = "at_100")]
+        bar: Entries,
+    }
+
+    #[test]
+    fn test_expiring() {
+        let value = to_value(Foo {
+            bar: [
+                ("a", 1, UNIX_EPOCH + Duration::from_secs(150)),
+                ("b", 2, UNIX_EPOCH + Duration::from_secs(50)),
+                ("c", 3, UNIX_EPOCH + Duration::from_secs(100)),
+            ],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"a": 1}
+            })
+        );
+    }
+}
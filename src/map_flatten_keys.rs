@@ -0,0 +1,98 @@
+//! Serializes a two-level nested iterator into a flat serde map with composite keys.
+//!
+//! The outer iterator yields `(K1, U)` pairs,
+//! where `U` is itself an iterator of `(K2, V)` pairs,
+//! and the output is a map from `"{k1}{separator}{k2}"` to each value,
+//! e.g. `{"a": {"x": 1}}` is flattened into `{"a.x": 1}`.
+//! Each inner iterator is cloned when its outer entry is serialized.
+//!
+//! *This module requires the "map_flatten_keys" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! let nested = vec![("a", vec![("x", 1), ("y", 2)]), ("b", vec![("z", 3)])];
+//!
+//! let mut buf = Vec::new();
+//! serde_iter::map_flatten_keys::serialize(
+//!     &nested,
+//!     ".",
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), r#"{"a.x":1,"a.y":2,"b.z":3}"#);
+//! ```
+
+use core::fmt::Display;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, K1, U, K2, V>(
+    iter: &T,
+    separator: &str,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K1, U)> + Clone,
+    K1: Display,
+    U: IntoIterator<Item = (K2, V)> + Clone,
+    K2: Display,
+    V: Serialize,
+{
+    let mut map = serializer.serialize_map(None)?;
+    for (outer, inner) in iter.clone() {
+        for (key, value) in inner.clone() {
+            map.serialize_entry(&format_args!("{}{}{}", outer, separator, key), &value)?;
+        }
+    }
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_value};
+
+    type Nested = Vec<(&'static str, Vec<(&'static str, i32)>)>;
+
+    fn dotted<S: Serializer>(iter: &Nested, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, ".", serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(serialize_with = "dotted")]
+        bar: Nested,
+    }
+
+    #[test]
+    fn test_flatten() {
+        let value = to_value(Foo {
+            bar: vec![
+                ("a", vec![("x", 1), ("y", 2)]),
+                ("b", vec![]),
+                ("c", vec![("z", 3)]),
+            ],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"a.x": 1, "a.y": 2, "c.z": 3}
+            })
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo { bar: vec![] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {}
+            })
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Serializes an iterator of serializable 2-tuples into a nested serde map,
+//! grouping entries by a key function.
+//!
+//! The output is a map from each group to a map of the entries in that group,
+//! i.e. `{group: {key: value, ...}, ...}`.
+//! Groups are emitted in the order of their first appearance,
+//! and entries within a group keep their original order.
+//!
+//! *This module requires the "map_grouped" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! let entries = vec![("a.x", 1), ("b.y", 2), ("a.z", 3)];
+//!
+//! let mut buf = Vec::new();
+//! serde_iter::map_grouped::serialize(
+//!     &entries,
+//!     |key: &&str| key.split('.').next().unwrap_or_default().to_owned(),
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(
+//!     String::from_utf8(buf).unwrap(),
+//!     r#"{"a":{"a.x":1,"a.z":3},"b":{"b.y":2}}"#,
+//! );
+//! ```
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, K, V, F, G>(iter: &T, group_fn: F, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize,
+    V: Serialize,
+    F: Fn(&K) -> G,
+    G: Serialize + Eq + Hash + Clone,
+{
+    let mut indices = HashMap::new();
+    let mut groups: Vec<(G, Vec<(K, V)>)> = Vec::new();
+    for (key, value) in iter.clone() {
+        let group = group_fn(&key);
+        let index = *indices.entry(group.clone()).or_insert_with(|| {
+            groups.push((group, Vec::new()));
+            groups.len() - 1
+        });
+        if let Some((_, entries)) = groups.get_mut(index) {
+            entries.push((key, value));
+        }
+    }
+
+    let mut map = serializer.serialize_map(Some(groups.len()))?;
+    for (group, entries) in &groups {
+        map.serialize_entry(group, &Entries(entries))?;
+    }
+    map.end()
+}
+
+struct Entries<'a, K, V>(&'a [(K, V)]);
+
+impl<'a, K, V> Serialize for Entries<'a, K, V>
+where
+    K: Serialize,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in self.0 {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_value};
+
+    type Entries = [(&'static str, i32); 4];
+
+    fn by_prefix<S: Serializer>(iter: &Entries, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(
+            iter,
+            |key: &&str| key.split('.').next().unwrap_or_default().to_owned(),
+            serializer,
+        )
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(serialize_with = "by_prefix")]
+        bar: Entries,
+    }
+
+    #[test]
+    fn test_grouped() {
+        let value = to_value(Foo {
+            bar: [("a.x", 1), ("b.y", 2), ("a.z", 3), ("b.w", 4)],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {
+                    "a": {"a.x": 1, "a.z": 3},
+                    "b": {"b.y": 2, "b.w": 4}
+                }
+            })
+        );
+    }
+}
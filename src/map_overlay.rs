@@ -0,0 +1,160 @@
+//! Serializes an iterator of serializable 2-tuples into a serde map,
+//! with values overridden by a second map.
+//!
+//! This is useful for layered configuration,
+//! where a base layer is emitted with some entries replaced by an overlay layer.
+//!
+//! Entries from the base iterator are emitted in their original order,
+//! using the overlay value instead if the overlay contains the same key.
+//! Keys that only exist in the overlay are appended afterwards,
+//! in the iteration order of the overlay `HashMap`.
+//!
+//! *This module requires the "map_overlay" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! use std::collections::HashMap;
+//!
+//! let mut overlay = HashMap::new();
+//! overlay.insert("a", 9);
+//!
+//! let mut buf = Vec::new();
+//! serde_iter::map_overlay::serialize(
+//!     &vec![("a", 1), ("b", 2)],
+//!     &overlay,
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), r#"{"a":9,"b":2}"#);
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hash};
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, A, B, K, V>(
+    base: &A,
+    overlay: &HashMap<K, V, B>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    A: IntoIterator<Item = (K, V)> + Clone,
+    B: BuildHasher,
+    K: Serialize + Eq + Hash,
+    V: Serialize,
+{
+    let iter = base.clone().into_iter();
+    let mut map = serializer.serialize_map(Some(iter.size_hint().0.max(overlay.len())))?;
+    let mut overridden = HashSet::new();
+    for (key, value) in iter {
+        match overlay.get_key_value(&key) {
+            Some((overlay_key, overlay_value)) => {
+                map.serialize_entry(&key, overlay_value)?;
+                overridden.insert(overlay_key);
+            }
+            None => map.serialize_entry(&key, &value)?,
+        }
+    }
+    for (key, value) in overlay {
+        if !overridden.contains(key) {
+            map.serialize_entry(key, value)?;
+        }
+    }
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_value};
+
+    fn with_overlay<S: Serializer>(
+        base: &[(&'static str, i32); 2],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut overlay = HashMap::new();
+        overlay.insert("a", 9);
+        super::serialize(base, &overlay, serializer)
+    }
+
+    fn with_new_key<S: Serializer>(
+        base: &[(&'static str, i32); 2],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut overlay = HashMap::new();
+        overlay.insert("c", 3);
+        super::serialize(base, &overlay, serializer)
+    }
+
+    fn with_empty_overlay<S: Serializer>(
+        base: &[(&'static str, i32); 2],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        super::serialize(base, &HashMap::new(), serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(serialize_with = "with_overlay")]
+        bar: [(&'static str, i32); 2],
+    }
+
+    #[derive(Serialize)]
+    struct NewKey {
+        #[serde(serialize_with = "with_new_key")]
+        bar: [(&'static str, i32); 2],
+    }
+
+    #[derive(Serialize)]
+    struct Empty {
+        #[serde(serialize_with = "with_empty_overlay")]
+        bar: [(&'static str, i32); 2],
+    }
+
+    #[test]
+    fn test_overlay() {
+        let value = to_value(Foo {
+            bar: [("a", 1), ("b", 2)],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"a": 9, "b": 2}
+            })
+        );
+    }
+
+    #[test]
+    fn test_appended() {
+        let value = to_value(NewKey {
+            bar: [("a", 1), ("b", 2)],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"a": 1, "b": 2, "c": 3}
+            })
+        );
+    }
+
+    #[test]
+    fn test_empty_overlay() {
+        let value = to_value(Empty {
+            bar: [("a", 1), ("b", 2)],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"a": 1, "b": 2}
+            })
+        );
+    }
+}
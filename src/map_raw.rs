@@ -0,0 +1,90 @@
+//! Serializes an iterator of 2-tuples with pre-serialized JSON values into a serde map.
+//!
+//! Each value is a `serde_json::value::RawValue`,
+//! such as a `Box<RawValue>` or a `&RawValue`,
+//! and is emitted verbatim instead of being re-encoded.
+//! This preserves the formatting and numeric precision of the original JSON.
+//!
+//! Raw values are only passed through verbatim by `serde_json` serializers;
+//! other serializers see them as an opaque struct.
+//!
+//! *This module requires the "json" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! use serde_json::value::RawValue;
+//!
+//! #[derive(serde::Serialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::map_raw")]
+//!     bar: Vec<(&'static str, Box<RawValue>)>,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: vec![("pi", RawValue::from_string("3.14159265358979323846".to_owned()).unwrap())],
+//! };
+//! assert_eq!(
+//!     serde_json::to_string(&foo).unwrap(),
+//!     r#"{"bar":{"pi":3.14159265358979323846}}"#,
+//! );
+//! ```
+
+use std::borrow::Borrow;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::value::RawValue;
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, K, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize,
+    V: Borrow<RawValue>,
+{
+    let iter = iter.clone().into_iter();
+    let mut map = serializer.serialize_map(Some(iter.size_hint().0))?;
+    for (key, value) in iter {
+        map.serialize_entry(&key, value.borrow())?;
+    }
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde_json::to_string;
+    use serde_json::value::RawValue;
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(with = "super")]
+        bar: Vec<(&'static str, Box<RawValue>)>,
+    }
+
+    fn raw(json: &str) -> Box<RawValue> {
+        RawValue::from_string(json.to_owned()).expect("Invalid JSON")
+    }
+
+    #[test]
+    fn test_empty() {
+        let json = to_string(&Foo { bar: vec![] });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(json, r#"{"bar":{}}"#);
+    }
+
+    #[test]
+    fn test_raw() {
+        let json = to_string(&Foo {
+            bar: vec![
+                ("object", raw(r#"{ "x" : 1 }"#)),
+                ("number", raw("1.000000000000000000001")),
+            ],
+        });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(
+            json,
+            r#"{"bar":{"object":{ "x" : 1 },"number":1.000000000000000000001}}"#
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! Serializes an iterator of serializable 2-tuples into a serde map,
+//! redacting the values of sensitive keys.
+//!
+//! The value of each key in the sensitive set is replaced with the string `"<redacted>"`,
+//! while other entries are passed through unchanged.
+//! This prevents leaking secrets in serialized diagnostics.
+//!
+//! *This module requires the "map_redact" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! use std::collections::HashSet;
+//!
+//! let sensitive: HashSet<_> = vec!["password"].into_iter().collect();
+//!
+//! let mut buf = Vec::new();
+//! serde_iter::map_redact::serialize(
+//!     &vec![("user", "alice"), ("password", "hunter2")],
+//!     &sensitive,
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(
+//!     String::from_utf8(buf).unwrap(),
+//!     r#"{"user":"alice","password":"<redacted>"}"#,
+//! );
+//! ```
+
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hash};
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// The value emitted in place of redacted values.
+pub const REDACTED: &str = "<redacted>";
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, K, V, B>(
+    iter: &T,
+    sensitive: &HashSet<K, B>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize + Eq + Hash,
+    V: Serialize,
+    B: BuildHasher,
+{
+    let iter = iter.clone().into_iter();
+    let mut map = serializer.serialize_map(Some(iter.size_hint().0))?;
+    for (key, value) in iter {
+        if sensitive.contains(&key) {
+            map.serialize_entry(&key, REDACTED)?;
+        } else {
+            map.serialize_entry(&key, &value)?;
+        }
+    }
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_value};
+
+    type Entries = [(&'static str, &'static str); 2];
+
+    fn redact_password<S: Serializer>(iter: &Entries, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut sensitive = HashSet::new();
+        sensitive.insert("password");
+        super::serialize(iter, &sensitive, serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(serialize_with = "redact_password")]
+        bar: Entries,
+    }
+
+    #[test]
+    fn test_redact() {
+        let value = to_value(Foo {
+            bar: [("user", "alice"), ("password", "hunter2")],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"user": "alice", "password": "<redacted>"}
+            })
+        );
+    }
+
+    #[test]
+    fn test_no_sensitive_keys() {
+        let value = to_value(Foo {
+            bar: [("user", "alice"), ("email", "alice@example.com")],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"user": "alice", "email": "alice@example.com"}
+            })
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! Serializes a double-ended iterator of serializable 2-tuples into a serde map,
+//! in reverse iteration order.
+//!
+//! This gives descending-key objects from ordered collections such as `BTreeMap`
+//! without reversing or copying the source collection.
+//! The map length is passed to the serializer if the size hint of the iterator is exact,
+//! and left unknown otherwise.
+//!
+//! *This module requires the "map_reverse" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! #[derive(serde::Serialize)]
+//! struct Foo<'a> {
+//!     #[serde(with = "serde_iter::map_reverse")]
+//!     bar: &'a BTreeMap<i32, &'static str>,
+//! }
+//!
+//! let mut map = BTreeMap::new();
+//! map.insert(1, "a");
+//! map.insert(2, "b");
+//!
+//! assert_eq!(
+//!     serde_json::to_string(&Foo { bar: &map }).unwrap(),
+//!     r#"{"bar":{"2":"b","1":"a"}}"#,
+//! );
+//! ```
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, K, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    T::IntoIter: DoubleEndedIterator,
+    K: Serialize,
+    V: Serialize,
+{
+    let iter = iter.clone().into_iter().rev();
+    let len = match iter.size_hint() {
+        (lower, Some(upper)) if lower == upper => Some(lower),
+        _ => None,
+    };
+    let mut map = serializer.serialize_map(len)?;
+    for (key, value) in iter {
+        map.serialize_entry(&key, &value)?;
+    }
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Serialize;
+    use serde_json::to_string;
+
+    #[derive(Serialize)]
+    struct Foo<T>
+    where
+        T: IntoIterator<Item = (i32, i32)> + Clone,
+        T::IntoIter: DoubleEndedIterator,
+    {
+        #[serde(with = "super")]
+        bar: T,
+    }
+
+    #[test]
+    fn test_empty() {
+        let json = to_string(&Foo { bar: vec![] });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(json, r#"{"bar":{}}"#);
+    }
+
+    #[test]
+    fn test_btree_map() {
+        let map: BTreeMap<i32, i32> = (1..=4).map(|key| (key, key * 10)).collect();
+        let json = to_string(&Foo { bar: map });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(json, r#"{"bar":{"4":40,"3":30,"2":20,"1":10}}"#);
+    }
+}
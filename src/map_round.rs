@@ -0,0 +1,170 @@
+//! Serializes an iterator of 2-tuples with `f64` values into a serde map,
+//! rounding each value to a number of decimal places.
+//!
+//! This avoids noisy last-digit differences in snapshots.
+//! Rounding is performed on the shortest decimal representation of each value,
+//! i.e. the digits printed by `Display`,
+//! so `1.005` rounds to `1.01` with [`Rounding::HalfUp`](enum.Rounding.html)
+//! even though the nearest `f64` is slightly below `1.005`.
+//! Infinite and `NaN` values are emitted unchanged.
+//!
+//! *This module requires the "map_round" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! use serde_iter::map_round::Rounding;
+//!
+//! let mut buf = Vec::new();
+//! serde_iter::map_round::serialize(
+//!     &vec![("a", 1.005), ("b", 2.0 / 3.0)],
+//!     2,
+//!     Rounding::HalfUp,
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), r#"{"a":1.01,"b":0.67}"#);
+//! ```
+
+use std::convert::TryFrom;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// The rounding mode for values exactly halfway between two candidates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Rounds halfway values away from zero, so `0.125` rounds to `0.13`.
+    HalfUp,
+    /// Rounds halfway values to the candidate with an even last digit, so `0.125` rounds to `0.12`.
+    HalfEven,
+}
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, K>(
+    iter: &T,
+    places: u32,
+    rounding: Rounding,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, f64)> + Clone,
+    K: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut map = serializer.serialize_map(Some(iter.size_hint().0))?;
+    for (key, value) in iter {
+        map.serialize_entry(&key, &round(value, places, rounding))?;
+    }
+    map.end()
+}
+
+fn round(value: f64, places: u32, rounding: Rounding) -> f64 {
+    if !value.is_finite() {
+        return value;
+    }
+
+    // `{:e}` prints the shortest digits that round-trip, e.g. `1.005e0`.
+    let formatted = format!("{:e}", value.abs());
+    let (mantissa, exponent) = match formatted.split_once('e') {
+        Some(split) => split,
+        None => return value,
+    };
+    let exponent: i64 = match exponent.parse() {
+        Ok(exponent) => exponent,
+        Err(_) => return value,
+    };
+    let digits: Vec<u8> = mantissa
+        .bytes()
+        .filter(u8::is_ascii_digit)
+        .map(|digit| digit - b'0')
+        .collect();
+
+    // The number of digits to keep, counting from the first significant digit.
+    let keep = exponent.saturating_add(1).saturating_add(i64::from(places));
+    let keep = match usize::try_from(keep) {
+        Ok(keep) if keep >= digits.len() => return value,
+        Ok(keep) => keep,
+        Err(_) => return 0.0,
+    };
+    let (kept, rest) = digits.split_at(keep);
+
+    let mut rounded = kept
+        .iter()
+        .fold(0_u64, |acc, &digit| acc * 10 + u64::from(digit));
+    let round_up = match rest.split_first() {
+        Some((&first, tail)) if first == 5 && tail.iter().all(|&digit| digit == 0) => {
+            match rounding {
+                Rounding::HalfUp => true,
+                Rounding::HalfEven => !rounded.is_multiple_of(2),
+            }
+        }
+        Some((&first, _)) => first >= 5,
+        None => false,
+    };
+    if round_up {
+        rounded += 1;
+    }
+    if rounded == 0 {
+        return 0.0;
+    }
+
+    let magnitude: f64 = format!("{}e-{}", rounded, places)
+        .parse()
+        .unwrap_or(value.abs());
+    magnitude.copysign(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+    use serde_json::to_string;
+
+    use super::Rounding;
+
+    type Entries = [(&'static str, f64); 4];
+
+    fn half_up<S: Serializer>(iter: &Entries, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, 2, Rounding::HalfUp, serializer)
+    }
+
+    fn half_even<S: Serializer>(iter: &Entries, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, 2, Rounding::HalfEven, serializer)
+    }
+
+    #[derive(Serialize)]
+    struct HalfUp {
+        #[serde(serialize_with = "half_up")]
+        bar: Entries,
+    }
+
+    #[derive(Serialize)]
+    struct HalfEven {
+        #[serde(serialize_with = "half_even")]
+        bar: Entries,
+    }
+
+    const ENTRIES: Entries = [("a", 1.005), ("b", 0.125), ("c", -2.675), ("d", 0.001)];
+
+    #[test]
+    fn test_half_up() {
+        let json = to_string(&HalfUp { bar: ENTRIES });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(json, r#"{"bar":{"a":1.01,"b":0.13,"c":-2.68,"d":0.0}}"#);
+    }
+
+    #[test]
+    fn test_half_even() {
+        let json = to_string(&HalfEven { bar: ENTRIES });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(json, r#"{"bar":{"a":1.0,"b":0.12,"c":-2.68,"d":0.0}}"#);
+    }
+
+    #[test]
+    fn test_unchanged() {
+        let json = to_string(&HalfUp {
+            bar: [("a", 1.5), ("b", 1e300), ("c", f64::INFINITY), ("d", 123.0)],
+        });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(json, r#"{"bar":{"a":1.5,"b":1e+300,"c":null,"d":123.0}}"#);
+    }
+}
@@ -0,0 +1,299 @@
+//! Serializes an iterator of 2-tuples with `Arc` values into a serde map,
+//! serializing each shared value only once.
+//!
+//! The output is `{"pool": [...], "entries": {key: index, ...}}`,
+//! where `pool` contains each distinct `Arc` once, in the order of its first appearance,
+//! and each entry maps its key to the index of its value in `pool`.
+//! Values are distinguished by pointer identity rather than equality,
+//! so this avoids re-serializing large values shared by many keys.
+//!
+//! The `deserialize` function restores the sharing:
+//! all keys referring to the same pool index receive clones of the same `Arc`.
+//!
+//! *This module requires the "map_shared" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! use std::sync::Arc;
+//!
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::map_shared")]
+//!     bar: Vec<(String, Arc<String>)>,
+//! }
+//!
+//! let shared = Arc::new("large".to_owned());
+//! let foo = Foo {
+//!     bar: vec![("a".to_owned(), shared.clone()), ("b".to_owned(), shared)],
+//! };
+//! let value = serde_json::to_value(&foo).unwrap();
+//! assert_eq!(value, serde_json::json!({
+//!     "bar": {"pool": ["large"], "entries": {"a": 0, "b": 0}}
+//! }));
+//!
+//! let foo: Foo = serde_json::from_value(value).unwrap();
+//! assert!(Arc::ptr_eq(&foo.bar[0].1, &foo.bar[1].1));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, SerializeStruct, Serializer};
+
+const FIELDS: &[&str] = &["pool", "entries"];
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, K, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, Arc<V>)> + Clone,
+    K: Serialize,
+    V: Serialize,
+{
+    let mut indices = HashMap::new();
+    let mut pool = Vec::new();
+    let mut entries = Vec::new();
+    for (key, value) in iter.clone() {
+        let index = *indices.entry(Arc::as_ptr(&value)).or_insert_with(|| {
+            pool.push(Arc::clone(&value));
+            pool.len() - 1
+        });
+        entries.push((key, index));
+    }
+
+    let mut st = serializer.serialize_struct("Shared", 2)?;
+    st.serialize_field("pool", &Pool(&pool))?;
+    st.serialize_field("entries", &Entries(entries))?;
+    st.end()
+}
+
+struct Pool<'a, V>(&'a [Arc<V>]);
+
+impl<'a, V> Serialize for Pool<'a, V>
+where
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for value in self.0 {
+            seq.serialize_element(&**value)?;
+        }
+        seq.end()
+    }
+}
+
+/// The entries of the map, in order, with values replaced by pool indices.
+struct Entries<K>(Vec<(K, usize)>);
+
+impl<K> Serialize for Entries<K>
+where
+    K: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, index) in &self.0 {
+            map.serialize_entry(key, index)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de, K> Deserialize<'de> for Entries<K>
+where
+    K: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(EntriesVisitor(PhantomData))
+    }
+}
+
+struct EntriesVisitor<K>(PhantomData<K>);
+
+impl<'de, K> Visitor<'de> for EntriesVisitor<K>
+where
+    K: Deserialize<'de>,
+{
+    type Value = Entries<K>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map from keys to pool indices")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries =
+            Vec::with_capacity(crate::size_hint::cautious::<(K, usize)>(map.size_hint()));
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Entries(entries))
+    }
+}
+
+/// Refer to the [module-level documentation](index.html).
+///
+/// Returns an error if an entry refers to an index out of bounds for the pool.
+pub fn deserialize<'de, D, C, K, V>(deserializer: D) -> Result<C, D::Error>
+where
+    D: Deserializer<'de>,
+    C: FromIterator<(K, Arc<V>)>,
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    deserializer.deserialize_struct("Shared", FIELDS, SharedVisitor(PhantomData))
+}
+
+struct SharedVisitor<C, K, V>(PhantomData<(C, K, V)>);
+
+impl<'de, C, K, V> Visitor<'de> for SharedVisitor<C, K, V>
+where
+    C: FromIterator<(K, Arc<V>)>,
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = C;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a pool of shared values and a map of entries referring to it")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<C, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let pool = seq
+            .next_element::<Vec<V>>()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let entries = seq
+            .next_element::<Entries<K>>()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        resolve(pool, entries)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<C, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut pool = None;
+        let mut entries = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "pool" => {
+                    if pool.is_some() {
+                        return Err(de::Error::duplicate_field("pool"));
+                    }
+                    pool = Some(map.next_value::<Vec<V>>()?);
+                }
+                "entries" => {
+                    if entries.is_some() {
+                        return Err(de::Error::duplicate_field("entries"));
+                    }
+                    entries = Some(map.next_value::<Entries<K>>()?);
+                }
+                other => return Err(de::Error::unknown_field(other, FIELDS)),
+            }
+        }
+        let pool = pool.ok_or_else(|| de::Error::missing_field("pool"))?;
+        let entries = entries.ok_or_else(|| de::Error::missing_field("entries"))?;
+        resolve(pool, entries)
+    }
+}
+
+fn resolve<C, K, V, E>(pool: Vec<V>, entries: Entries<K>) -> Result<C, E>
+where
+    C: FromIterator<(K, Arc<V>)>,
+    E: de::Error,
+{
+    let pool: Vec<Arc<V>> = pool.into_iter().map(Arc::new).collect();
+    entries
+        .0
+        .into_iter()
+        .map(|(key, index)| match pool.get(index) {
+            Some(value) => Ok((key, Arc::clone(value))),
+            None => Err(E::custom(format_args!(
+                "pool index {} is out of bounds for a pool of {} values",
+                index,
+                pool.len()
+            ))),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::{from_value, json, to_value};
+
+    #[derive(Serialize, Deserialize)]
+    struct Foo {
+        #[serde(with = "super")]
+        bar: BTreeMap<String, Arc<Vec<i32>>>,
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let shared = Arc::new(vec![1, 2, 3]);
+        let mut bar = BTreeMap::new();
+        bar.insert("a".to_owned(), Arc::clone(&shared));
+        bar.insert("b".to_owned(), Arc::new(vec![4]));
+        bar.insert("c".to_owned(), shared);
+
+        let value = to_value(Foo { bar });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {
+                    "pool": [[1, 2, 3], [4]],
+                    "entries": {"a": 0, "b": 1, "c": 0}
+                }
+            })
+        );
+
+        let foo: Foo = from_value(value).expect("Failed to deserialize");
+        match (foo.bar.get("a"), foo.bar.get("b"), foo.bar.get("c")) {
+            (Some(a), Some(b), Some(c)) => {
+                assert!(Arc::ptr_eq(a, c));
+                assert!(!Arc::ptr_eq(a, b));
+                assert_eq!(**a, vec![1, 2, 3]);
+                assert_eq!(**b, vec![4]);
+            }
+            _ => panic!("Missing entries"),
+        }
+    }
+
+    #[test]
+    fn test_out_of_bounds() {
+        let result = from_value::<Foo>(json!({
+            "bar": {"pool": [[1]], "entries": {"a": 1}}
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_deserialize_untrusted_length() {
+        // An empty pool, followed by a length prefix for the entries
+        // that claims far more entries than could ever be allocated.
+        let mut input = 0_u64.to_le_bytes().to_vec();
+        input.extend_from_slice(&0x0fff_ffff_ffff_ffff_u64.to_le_bytes());
+        assert!(bincode::deserialize::<Foo>(&input).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+//! Serializes an iterator of serializable 2-tuples into a serde map,
+//! ordered by value descending and then by key ascending.
+//!
+//! This is useful for leaderboard-like output,
+//! where the highest values come first.
+//! Breaking ties by key makes the output fully deterministic
+//! regardless of the iteration order of the input.
+//!
+//! The entries are collected and sorted before being serialized.
+//!
+//! *This module requires the "map_sorted_by_value_then_key" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! let mut buf = Vec::new();
+//! serde_iter::map_sorted_by_value_then_key::serialize(
+//!     &vec![("carol", 5), ("bob", 7), ("alice", 5)],
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), r#"{"bob":7,"alice":5,"carol":5}"#);
+//! ```
+
+use std::cmp::Reverse;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, K, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize + Ord,
+    V: Serialize + Ord,
+{
+    let mut entries: Vec<(K, V)> = iter.clone().into_iter().collect();
+    entries.sort_by(|(a_key, a_value), (b_key, b_value)| {
+        (Reverse(a_value), a_key).cmp(&(Reverse(b_value), b_key))
+    });
+
+    let mut map = serializer.serialize_map(Some(entries.len()))?;
+    for (key, value) in &entries {
+        map.serialize_entry(key, value)?;
+    }
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde_json::to_string;
+
+    #[derive(Serialize)]
+    struct Foo<T>
+    where
+        T: IntoIterator<Item = (&'static str, u32)> + Clone,
+    {
+        #[serde(with = "super")]
+        bar: T,
+    }
+
+    #[test]
+    fn test_empty() {
+        let json = to_string(&Foo { bar: vec![] });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(json, r#"{"bar":{}}"#);
+    }
+
+    #[test]
+    fn test_tiebreak() {
+        let json = to_string(&Foo {
+            bar: vec![("d", 1), ("c", 3), ("b", 1), ("a", 3), ("e", 2)],
+        });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(json, r#"{"bar":{"a":3,"c":3,"e":2,"b":1,"d":1}}"#);
+    }
+}
@@ -0,0 +1,202 @@
+//! Serializes an iterator of serializable 2-tuples into a nested serde map,
+//! placing each value at the path computed from its key.
+//!
+//! The path function splits each key into its path segments,
+//! e.g. `"a.b.c"` into `["a", "b", "c"]`,
+//! and the value is emitted under the nested objects named by the segments,
+//! i.e. `{"a": {"b": {"c": value}}}`.
+//! This builds hierarchical configuration from flat keys with custom splitting.
+//!
+//! Segments are emitted in the order of their first appearance.
+//! Serialization fails if a path is empty,
+//! or if a path is a prefix of another path or equal to it,
+//! since the same segment cannot hold both a value and nested entries.
+//!
+//! *This module requires the "map_tree" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! let mut buf = Vec::new();
+//! serde_iter::map_tree::serialize(
+//!     &vec![("server.host", "localhost"), ("server.port", "80"), ("name", "foo")],
+//!     |key: &&str| key.split('.').map(str::to_owned).collect(),
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(
+//!     String::from_utf8(buf).unwrap(),
+//!     r#"{"server":{"host":"localhost","port":"80"},"name":"foo"}"#,
+//! );
+//! ```
+
+use std::collections::HashMap;
+
+use serde::ser::{Error, Serialize, SerializeMap, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, K, V, F>(iter: &T, path_fn: F, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    V: Serialize,
+    F: Fn(&K) -> Vec<String>,
+{
+    let mut root = Children::default();
+    for (key, value) in iter.clone() {
+        let path = path_fn(&key);
+        root.insert(&path, value).map_err(S::Error::custom)?;
+    }
+    root.serialize(serializer)
+}
+
+enum Node<V> {
+    Leaf(V),
+    Branch(Children<V>),
+}
+
+/// The children of a branch, in the order of their first appearance.
+struct Children<V> {
+    entries: Vec<(String, Node<V>)>,
+    /// The position of each segment in `entries`.
+    index: HashMap<String, usize>,
+}
+
+impl<V> Default for Children<V> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+}
+
+impl<V> Children<V> {
+    fn insert(&mut self, path: &[String], value: V) -> Result<(), String> {
+        let (segment, rest) = match path.split_first() {
+            Some(split) => split,
+            None => return Err("empty path".to_owned()),
+        };
+
+        let position = self.index.get(segment).copied();
+        match (
+            position.and_then(|index| self.entries.get_mut(index)),
+            rest.is_empty(),
+        ) {
+            (None, true) => self.push(segment, Node::Leaf(value)),
+            (None, false) => {
+                let mut grandchildren = Self::default();
+                grandchildren.insert(rest, value)?;
+                self.push(segment, Node::Branch(grandchildren));
+            }
+            (Some((_, Node::Branch(grandchildren))), false) => grandchildren.insert(rest, value)?,
+            (Some(_), _) => {
+                return Err(format!("conflicting entries at path segment {:?}", segment))
+            }
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, segment: &str, node: Node<V>) {
+        self.index.insert(segment.to_owned(), self.entries.len());
+        self.entries.push((segment.to_owned(), node));
+    }
+}
+
+impl<V> Serialize for Children<V>
+where
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.entries.len()))?;
+        for (segment, node) in &self.entries {
+            match node {
+                Node::Leaf(value) => map.serialize_entry(segment, value)?,
+                Node::Branch(children) => map.serialize_entry(segment, children)?,
+            }
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+    use serde_json::{to_string, to_value};
+
+    type Entries = [(&'static str, i32); 4];
+
+    fn by_slash<S: Serializer>(iter: &Entries, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(
+            iter,
+            |key: &&str| {
+                key.split('/')
+                    .filter(|segment| !segment.is_empty())
+                    .map(str::to_owned)
+                    .collect()
+            },
+            serializer,
+        )
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(serialize_with = "by_slash")]
+        bar: Entries,
+    }
+
+    #[test]
+    fn test_tree() {
+        let json = to_string(&Foo {
+            bar: [("/a/b/c", 1), ("/a/b/d", 2), ("/a/e", 3), ("/f", 4)],
+        });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(json, r#"{"bar":{"a":{"b":{"c":1,"d":2},"e":3},"f":4}}"#);
+    }
+
+    #[test]
+    fn test_conflict() {
+        for bar in [
+            [("/a", 1), ("/a/b", 2), ("/c", 3), ("/d", 4)],
+            [("/a/b", 1), ("/a", 2), ("/c", 3), ("/d", 4)],
+            [("/a", 1), ("/a", 2), ("/c", 3), ("/d", 4)],
+            [("/", 1), ("/b", 2), ("/c", 3), ("/d", 4)],
+        ] {
+            assert!(to_value(Foo { bar }).is_err());
+        }
+    }
+
+    #[test]
+    fn test_many_siblings() {
+        fn by_dot<S: Serializer>(
+            iter: &Vec<(String, usize)>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            super::serialize(
+                iter,
+                |key: &String| key.split('.').map(str::to_owned).collect(),
+                serializer,
+            )
+        }
+
+        #[derive(Serialize)]
+        struct Wide {
+            #[serde(serialize_with = "by_dot")]
+            bar: Vec<(String, usize)>,
+        }
+
+        let bar: Vec<_> = (0..10_000)
+            .map(|i| (format!("root.{}", i), i))
+            .chain(std::iter::once(("root.0".to_owned(), 0)))
+            .collect();
+        assert!(to_value(Wide { bar: bar.clone() }).is_err());
+
+        let value = to_value(Wide {
+            bar: bar.into_iter().take(10_000).collect(),
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value.pointer("/bar/root/9999"), Some(&9999.into()));
+    }
+}
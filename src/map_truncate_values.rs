@@ -0,0 +1,94 @@
+//! Serializes an iterator of 2-tuples with string values into a serde map,
+//! truncating long values.
+//!
+//! Each value longer than the maximum length is cut to that many characters
+//! and suffixed with `"…"`, while shorter values are emitted unchanged.
+//! Lengths are counted in `char`s, so multi-byte characters are never split.
+//! This keeps diagnostic maps readable.
+//!
+//! *This module requires the "map_truncate_values" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! let mut buf = Vec::new();
+//! serde_iter::map_truncate_values::serialize(
+//!     &vec![("short", "abc"), ("long", "abcdefgh")],
+//!     5,
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(
+//!     String::from_utf8(buf).unwrap(),
+//!     r#"{"short":"abc","long":"abcde…"}"#,
+//! );
+//! ```
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// The marker appended to truncated values.
+pub const ELLIPSIS: &str = "…";
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, K, V>(iter: &T, max_len: usize, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize,
+    V: AsRef<str>,
+{
+    let iter = iter.clone().into_iter();
+    let mut map = serializer.serialize_map(Some(iter.size_hint().0))?;
+    for (key, value) in iter {
+        let value = value.as_ref();
+        match value.char_indices().nth(max_len) {
+            Some((end, _)) => {
+                let prefix = value.get(..end).unwrap_or(value);
+                map.serialize_entry(&key, &format_args!("{}{}", prefix, ELLIPSIS))?;
+            }
+            None => map.serialize_entry(&key, value)?,
+        }
+    }
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_value};
+
+    type Entries = [(&'static str, &'static str); 4];
+
+    fn max_4<S: Serializer>(iter: &Entries, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, 4, serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(serialize_with = "max_4")]
+        bar: Entries,
+    }
+
+    #[test]
+    fn test_truncate() {
+        let value = to_value(Foo {
+            bar: [
+                ("short", "abc"),
+                ("exact", "abcd"),
+                ("long", "abcdefgh"),
+                ("unicode", "äöüßé"),
+            ],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {
+                    "short": "abc",
+                    "exact": "abcd",
+                    "long": "abcd…",
+                    "unicode": "äöüß…"
+                }
+            })
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! Serializes an iterator of 2-tuples with tagged numeric values into a serde map,
+//! formatting each value according to its tag.
+//!
+//! The tag of each [`Typed`](enum.Typed.html) value selects how it is displayed,
+//! which centralizes display formatting for report generation:
+//!
+//! - `Currency(1.0)` is emitted as the string `"$1.00"`, always with two decimal places.
+//! - `Percent(0.5)` is emitted as the string `"50%"`,
+//!   with up to two decimal places and trailing zeros removed.
+//! - `Raw(0.5)` is emitted as the number `0.5`, unformatted.
+//!
+//! *This module requires the "map_typed" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! use serde_iter::map_typed::Typed;
+//!
+//! #[derive(serde::Serialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::map_typed")]
+//!     bar: Vec<(&'static str, Typed)>,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: vec![
+//!         ("price", Typed::Currency(1.0)),
+//!         ("discount", Typed::Percent(0.125)),
+//!         ("weight", Typed::Raw(2.5)),
+//!     ],
+//! };
+//! assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+//!     "bar": {"price": "$1.00", "discount": "12.5%", "weight": 2.5}
+//! }));
+//! ```
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// A numeric value tagged with its display format.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Typed {
+    /// An amount of money in dollars, formatted like `"$1.00"`.
+    Currency(f64),
+    /// A fraction, formatted as a percentage like `"50%"`.
+    Percent(f64),
+    /// A number emitted without formatting.
+    Raw(f64),
+}
+
+impl Serialize for Typed {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            Typed::Currency(value) if value.is_sign_negative() && value != 0.0 => {
+                serializer.collect_str(&format_args!("-${:.2}", -value))
+            }
+            Typed::Currency(value) => serializer.collect_str(&format_args!("${:.2}", value)),
+            Typed::Percent(value) => {
+                let formatted = format!("{:.2}", value * 100.0);
+                let trimmed = if formatted.contains('.') {
+                    formatted.trim_end_matches('0').trim_end_matches('.')
+                } else {
+                    &formatted
+                };
+                serializer.collect_str(&format_args!("{}%", trimmed))
+            }
+            Typed::Raw(value) => serializer.serialize_f64(value),
+        }
+    }
+}
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, K>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, Typed)> + Clone,
+    K: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut map = serializer.serialize_map(Some(iter.size_hint().0))?;
+    for (key, value) in iter {
+        map.serialize_entry(&key, &value)?;
+    }
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde_json::{json, to_value};
+
+    use super::Typed;
+
+    #[derive(Serialize)]
+    struct Foo<T>
+    where
+        T: IntoIterator<Item = (&'static str, Typed)> + Clone,
+    {
+        #[serde(with = "super")]
+        bar: T,
+    }
+
+    #[test]
+    fn test_typed() {
+        let value = to_value(Foo {
+            bar: vec![
+                ("currency", Typed::Currency(1.0)),
+                ("percent", Typed::Percent(0.5)),
+                ("raw", Typed::Raw(0.5)),
+            ],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"currency": "$1.00", "percent": "50%", "raw": 0.5}
+            })
+        );
+    }
+
+    #[test]
+    fn test_rounding() {
+        let value = to_value(Foo {
+            bar: vec![
+                ("refund", Typed::Currency(-2.5)),
+                ("cents", Typed::Currency(0.996)),
+                ("third", Typed::Percent(1.0 / 3.0)),
+                ("small", Typed::Percent(0.07)),
+            ],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"refund": "-$2.50", "cents": "$1.00", "third": "33.33%", "small": "7%"}
+            })
+        );
+    }
+}
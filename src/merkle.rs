@@ -0,0 +1,148 @@
+//! Serializes an iterator of serializables into a struct of the elements and their Merkle root.
+//!
+//! The output is `{"items": [...], "root": "<hex>"}`,
+//! where `root` is the lowercase hex SHA-256 Merkle root over the elements.
+//! Consumers can recompute the root to verify that the items were not altered.
+//!
+//! Each leaf is the hash of the JSON encoding of an element,
+//! and leaves and inner nodes are domain-separated as in RFC 6962:
+//! a leaf is `SHA-256(0x00 || json)` and an inner node is `SHA-256(0x01 || left || right)`.
+//! If a level has an odd number of nodes, the last node is promoted to the next level unchanged.
+//! The root of an empty sequence is `SHA-256("")`.
+//!
+//! The iterator is cloned twice: once to compute the root and once to serialize the items.
+//!
+//! *This module requires the "merkle" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::merkle")]
+//!     bar: Vec<i32>,
+//! }
+//!
+//! let value = serde_json::to_value(&Foo { bar: vec![1, 2, 3] }).unwrap();
+//! assert_eq!(value["bar"]["items"], serde_json::json!([1, 2, 3]));
+//! assert_eq!(value["bar"]["root"].as_str().unwrap().len(), 64);
+//! ```
+
+use serde::ser::{Error, Serialize, SerializeSeq, SerializeStruct, Serializer};
+use sha2::{Digest, Sha256};
+
+use crate::digest;
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    let mut level = Vec::new();
+    for value in iter.clone() {
+        let leaf = digest::content_digest(&[0x00], &value).map_err(S::Error::custom)?;
+        level.push(leaf);
+    }
+    let root = merkle_root(level);
+
+    let mut st = serializer.serialize_struct("Merkle", 2)?;
+    st.serialize_field("items", &Items(iter))?;
+    st.serialize_field("root", &digest::to_hex(&root))?;
+    st.end()
+}
+
+fn merkle_root(mut level: Vec<[u8; 32]>) -> [u8; 32] {
+    if level.is_empty() {
+        return Sha256::digest([]).into();
+    }
+
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => Sha256::new()
+                    .chain_update([0x01])
+                    .chain_update(left)
+                    .chain_update(right)
+                    .finalize()
+                    .into(),
+                [single] => *single,
+                _ => unreachable!("chunks(2) yields one or two nodes"),
+            })
+            .collect();
+    }
+    level.first().copied().unwrap_or_default()
+}
+
+struct Items<'a, T>(&'a T);
+
+impl<'a, T, V> Serialize for Items<'a, T>
+where
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let iter = self.0.clone().into_iter();
+        let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+        for value in iter {
+            seq.serialize_element(&value)?;
+        }
+        seq.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde_json::{json, to_value, Value};
+
+    #[derive(Serialize)]
+    struct Foo<T>
+    where
+        T: IntoIterator<Item = i32> + Clone,
+    {
+        #[serde(with = "super")]
+        bar: T,
+    }
+
+    fn root(items: Vec<i32>) -> String {
+        let value = to_value(Foo { bar: items.clone() });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value.pointer("/bar/items"), Some(&json!(items)));
+        match value.pointer("/bar/root") {
+            Some(Value::String(root)) => root.clone(),
+            _ => panic!("Expected a root string"),
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(
+            root(vec![]),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_stable() {
+        assert_eq!(root(vec![1, 2, 3]), root(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_changes() {
+        let original = root(vec![1, 2, 3, 4, 5]);
+        for index in 0..5 {
+            let mut items = vec![1, 2, 3, 4, 5];
+            if let Some(item) = items.get_mut(index) {
+                *item += 10;
+            }
+            assert_ne!(root(items), original);
+        }
+        assert_ne!(root(vec![1, 2, 3, 4]), original);
+        assert_ne!(root(vec![2, 1, 3, 4, 5]), original);
+    }
+}
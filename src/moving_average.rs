@@ -0,0 +1,188 @@
+//! Serializes an iterator of `f64` into a serde sequence of trailing moving averages.
+//!
+//! Each output element is the average of the corresponding input element
+//! and the `window - 1` elements before it.
+//! The first `window - 1` outputs do not have a full window;
+//! they are either averaged over the elements seen so far or skipped,
+//! as selected by [`Warmup`](enum.Warmup.html).
+//!
+//! *This module requires the "moving_average" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! use serde_iter::moving_average::Warmup;
+//!
+//! let mut buf = Vec::new();
+//! serde_iter::moving_average::serialize(
+//!     &vec![2.0, 4.0, 6.0],
+//!     2,
+//!     Warmup::Skip,
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), "[3.0,5.0]");
+//! ```
+
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+
+use serde::ser::{Error, SerializeSeq, Serializer};
+
+/// The behaviour for outputs before the window is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Warmup {
+    /// Emits the average of the elements seen so far.
+    Partial,
+    /// Omits the output.
+    Skip,
+}
+
+/// Refer to the [module-level documentation](index.html).
+///
+/// Returns an error if `window` is zero or greater than `u32::MAX`.
+pub fn serialize<S, T>(
+    iter: &T,
+    window: usize,
+    warmup: Warmup,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = f64> + Clone,
+{
+    if window == 0 || u32::try_from(window).is_err() {
+        return Err(S::Error::custom(
+            "moving average window must be between 1 and u32::MAX",
+        ));
+    }
+
+    let iter = iter.clone().into_iter();
+    let hint = match warmup {
+        Warmup::Partial => iter.size_hint().0,
+        Warmup::Skip => iter.size_hint().0.saturating_add(1).saturating_sub(window),
+    };
+    let mut seq = serializer.serialize_seq(Some(hint))?;
+    let mut buffer = VecDeque::with_capacity(window);
+    for value in iter {
+        if buffer.len() == window {
+            buffer.pop_front();
+        }
+        buffer.push_back(value);
+        if buffer.len() < window && warmup == Warmup::Skip {
+            continue;
+        }
+
+        let len = u32::try_from(buffer.len()).map_err(S::Error::custom)?;
+        let average = buffer.iter().sum::<f64>() / f64::from(len);
+        seq.serialize_element(&average)?;
+    }
+    seq.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_string, to_value};
+
+    use super::Warmup;
+
+    fn partial<S: Serializer>(iter: &[f64; 3], serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, 2, Warmup::Partial, serializer)
+    }
+
+    fn skip<S: Serializer>(iter: &[f64; 3], serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, 2, Warmup::Skip, serializer)
+    }
+
+    fn zero<S: Serializer>(iter: &[f64; 3], serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, 0, Warmup::Skip, serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Partial {
+        #[serde(serialize_with = "partial")]
+        bar: [f64; 3],
+    }
+
+    #[derive(Serialize)]
+    struct Skip {
+        #[serde(serialize_with = "skip")]
+        bar: [f64; 3],
+    }
+
+    #[derive(Serialize)]
+    struct Zero {
+        #[serde(serialize_with = "zero")]
+        bar: [f64; 3],
+    }
+
+    #[test]
+    fn test_partial() {
+        let value = to_value(Partial {
+            bar: [2.0, 4.0, 6.0],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": [2.0, 3.0, 5.0]
+            })
+        );
+    }
+
+    #[test]
+    fn test_skip() {
+        let value = to_value(Skip {
+            bar: [2.0, 4.0, 6.0],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": [3.0, 5.0]
+            })
+        );
+    }
+
+    #[test]
+    fn test_zero_window() {
+        assert!(to_value(Zero {
+            bar: [2.0, 4.0, 6.0]
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_huge_size_hint() {
+        #[derive(Clone)]
+        struct Liar(std::vec::IntoIter<f64>);
+
+        impl Iterator for Liar {
+            type Item = f64;
+
+            fn next(&mut self) -> Option<f64> {
+                self.0.next()
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (usize::MAX, None)
+            }
+        }
+
+        fn lying<S: Serializer>(iter: &Liar, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize(iter, 2, Warmup::Skip, serializer)
+        }
+
+        #[derive(Serialize)]
+        struct Lying {
+            #[serde(serialize_with = "lying")]
+            bar: Liar,
+        }
+
+        let json = to_string(&Lying {
+            bar: Liar(vec![2.0, 4.0, 6.0].into_iter()),
+        });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(json, r#"{"bar":[3.0,5.0]}"#);
+    }
+}
@@ -0,0 +1,416 @@
+//! Serializes an iterator of serializable 2-tuples with duplicate keys into a serde map of arrays.
+//!
+//! The values of each key are collected into an array in their original order,
+//! so `[("a", 1), ("a", 2), ("b", 3)]` is serialized as `{"a": [1, 2], "b": [3]}`.
+//! Keys are emitted in the order of their first appearance.
+//!
+//! The `serialize` function always emits arrays.
+//! Use [`serialize_single_as`](fn.serialize_single_as.html) to emit keys with a single value as
+//! a scalar instead.
+//!
+//! The `deserialize` function expands each array back into individual pairs,
+//! and collects them into any `FromIterator<(K, V)>` container.
+//! A value that is not an array is read as a single value,
+//! so both representations can be deserialized.
+//! As a result, a value type that is itself deserialized from an array is not supported.
+//! Telling the two apart needs a self-describing format such as JSON,
+//! so `deserialize` fails in formats such as bincode.
+//! Use [`deserialize_arrays`](fn.deserialize_arrays.html) to read the output of `serialize`
+//! in any format, which requires every value to be an array.
+//!
+//! *This module requires the "multimap" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::multimap")]
+//!     bar: Vec<(String, i32)>,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: vec![("a".into(), 1), ("a".into(), 2), ("b".into(), 3)],
+//! };
+//! let value = serde_json::to_value(&foo).unwrap();
+//! assert_eq!(value, serde_json::json!({
+//!     "bar": {"a": [1, 2], "b": [3]}
+//! }));
+//!
+//! let foo: Foo = serde_json::from_value(value).unwrap();
+//! assert_eq!(foo.bar, vec![("a".into(), 1), ("a".into(), 2), ("b".into(), 3)]);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+
+use serde::de::value::{
+    BorrowedStrDeserializer, MapAccessDeserializer, StrDeserializer, StringDeserializer,
+};
+use serde::de::{self, Deserialize, Deserializer, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+/// The representation of keys with a single value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SingleValue {
+    /// Emits the single value in an array, e.g. `{"b": [3]}`.
+    Array,
+    /// Emits the single value as a scalar, e.g. `{"b": 3}`.
+    Scalar,
+}
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, K, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize + Eq + Hash,
+    V: Serialize,
+{
+    serialize_single_as(iter, SingleValue::Array, serializer)
+}
+
+/// Serializes keys with a single value in the given representation.
+pub fn serialize_single_as<S, T, K, V>(
+    iter: &T,
+    single: SingleValue,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize + Eq + Hash,
+    V: Serialize,
+{
+    let groups = crate::groups::group::<HashMap<_, _>, _, _, _>(iter.clone());
+    let mut map = serializer.serialize_map(Some(groups.len()))?;
+    for (key, values) in &groups {
+        map.serialize_entry(key, &Values { values, single })?;
+    }
+    map.end()
+}
+
+struct Values<'a, V> {
+    values: &'a [V],
+    single: SingleValue,
+}
+
+impl<'a, V> Serialize for Values<'a, V>
+where
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if let (SingleValue::Scalar, [value]) = (self.single, self.values) {
+            return value.serialize(serializer);
+        }
+
+        let mut seq = serializer.serialize_seq(Some(self.values.len()))?;
+        for value in self.values {
+            seq.serialize_element(value)?;
+        }
+        seq.end()
+    }
+}
+
+/// Refer to the [module-level documentation](index.html).
+pub fn deserialize<'de, D, C, K, V>(deserializer: D) -> Result<C, D::Error>
+where
+    D: Deserializer<'de>,
+    C: FromIterator<(K, V)>,
+    K: Deserialize<'de> + Clone,
+    V: Deserialize<'de>,
+{
+    deserializer.deserialize_map(MultimapVisitor {
+        arrays: false,
+        marker: PhantomData,
+    })
+}
+
+/// Deserializes a map in which every value is an array.
+///
+/// Unlike [`deserialize`], this does not depend on the format being self-describing,
+/// so the output of [`serialize`] round-trips in every format.
+pub fn deserialize_arrays<'de, D, C, K, V>(deserializer: D) -> Result<C, D::Error>
+where
+    D: Deserializer<'de>,
+    C: FromIterator<(K, V)>,
+    K: Deserialize<'de> + Clone,
+    V: Deserialize<'de>,
+{
+    deserializer.deserialize_map(MultimapVisitor {
+        arrays: true,
+        marker: PhantomData,
+    })
+}
+
+struct MultimapVisitor<C, K, V> {
+    /// Whether every value must be an array.
+    arrays: bool,
+    marker: PhantomData<(C, K, V)>,
+}
+
+impl<'de, C, K, V> Visitor<'de> for MultimapVisitor<C, K, V>
+where
+    C: FromIterator<(K, V)>,
+    K: Deserialize<'de> + Clone,
+    V: Deserialize<'de>,
+{
+    type Value = C;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map of values or arrays of values")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<C, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut pairs = Vec::with_capacity(crate::size_hint::cautious::<(K, V)>(map.size_hint()));
+        while let Some(key) = map.next_key::<K>()? {
+            let values = if self.arrays {
+                map.next_value::<Vec<V>>()?
+            } else {
+                map.next_value::<OneOrMany<V>>()?.0
+            };
+            pairs.extend(values.into_iter().map(|value| (key.clone(), value)));
+        }
+        Ok(pairs.into_iter().collect())
+    }
+}
+
+/// An array of values, or a single value that is not an array.
+struct OneOrMany<V>(Vec<V>);
+
+impl<'de, V> Deserialize<'de> for OneOrMany<V>
+where
+    V: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(OneOrManyVisitor(PhantomData))
+    }
+}
+
+struct OneOrManyVisitor<V>(PhantomData<V>);
+
+impl<'de, V> OneOrManyVisitor<V>
+where
+    V: Deserialize<'de>,
+{
+    fn one<D, E>(deserializer: D) -> Result<OneOrMany<V>, E>
+    where
+        D: Deserializer<'de, Error = E>,
+    {
+        V::deserialize(deserializer).map(|value| OneOrMany(vec![value]))
+    }
+}
+
+impl<'de, V> Visitor<'de> for OneOrManyVisitor<V>
+where
+    V: Deserialize<'de>,
+{
+    type Value = OneOrMany<V>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a value or an array of values")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut values = Vec::with_capacity(crate::size_hint::cautious::<V>(seq.size_hint()));
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(OneOrMany(values))
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        Self::one(v.into_deserializer())
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Self::one(v.into_deserializer())
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Self::one(v.into_deserializer())
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        Self::one(v.into_deserializer())
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Self::one(StrDeserializer::new(v))
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        Self::one(BorrowedStrDeserializer::new(v))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Self::one(StringDeserializer::new(v))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Self::one(().into_deserializer())
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        Self::one(MapAccessDeserializer::new(map))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize, Serializer};
+    use serde_json::{from_str, json, to_string, to_value};
+
+    use super::SingleValue;
+
+    type Entries = Vec<(&'static str, i32)>;
+
+    fn scalar<S: Serializer>(iter: &Entries, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize_single_as(iter, SingleValue::Scalar, serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(with = "super")]
+        bar: Entries,
+    }
+
+    #[derive(Serialize)]
+    struct Scalar {
+        #[serde(serialize_with = "scalar")]
+        bar: Entries,
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo { bar: vec![] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {}
+            })
+        );
+    }
+
+    #[test]
+    fn test_collapse() {
+        let value = to_value(Foo {
+            bar: vec![("a", 1), ("b", 3), ("a", 2)],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"a": [1, 2], "b": [3]}
+            })
+        );
+    }
+
+    #[test]
+    fn test_scalar_single() {
+        let value = to_value(Scalar {
+            bar: vec![("a", 1), ("b", 3), ("a", 2)],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"a": [1, 2], "b": 3}
+            })
+        );
+    }
+
+    #[test]
+    fn test_first_appearance_order() {
+        let json = to_string(&Foo {
+            bar: vec![("b", 1), ("a", 2), ("b", 3)],
+        });
+        let json = json.expect("Failed to serialize");
+        assert_eq!(json, r#"{"bar":{"b":[1,3],"a":[2]}}"#);
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct RoundTrip {
+        #[serde(with = "super")]
+        bar: Vec<(String, i32)>,
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let foo = RoundTrip {
+            bar: vec![("a".into(), 1), ("b".into(), 3), ("a".into(), 2)],
+        };
+        let json = to_string(&foo).expect("Failed to serialize");
+        assert_eq!(json, r#"{"bar":{"a":[1,2],"b":[3]}}"#);
+
+        let decoded: RoundTrip = from_str(&json).expect("Failed to deserialize");
+        assert_eq!(
+            decoded,
+            RoundTrip {
+                bar: vec![("a".into(), 1), ("a".into(), 2), ("b".into(), 3)],
+            }
+        );
+    }
+
+    #[test]
+    fn test_deserialize_scalar() {
+        let foo: RoundTrip =
+            from_str(r#"{"bar":{"a":[1,2],"b":3}}"#).expect("Failed to deserialize");
+        assert_eq!(
+            foo,
+            RoundTrip {
+                bar: vec![("a".into(), 1), ("a".into(), 2), ("b".into(), 3)],
+            }
+        );
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Arrays {
+        #[serde(
+            serialize_with = "super::serialize",
+            deserialize_with = "super::deserialize_arrays"
+        )]
+        bar: Vec<(String, i32)>,
+    }
+
+    #[test]
+    fn test_deserialize_arrays_bincode() {
+        let foo = Arrays {
+            bar: vec![("a".into(), 1), ("b".into(), 3), ("a".into(), 2)],
+        };
+        let encoded = bincode::serialize(&foo).expect("Failed to serialize");
+        assert!(bincode::deserialize::<RoundTrip>(&encoded).is_err());
+
+        let decoded: Arrays = bincode::deserialize(&encoded).expect("Failed to deserialize");
+        assert_eq!(
+            decoded,
+            Arrays {
+                bar: vec![("a".into(), 1), ("a".into(), 2), ("b".into(), 3)],
+            }
+        );
+    }
+
+    #[test]
+    fn test_deserialize_arrays_rejects_scalar() {
+        let foo = from_str::<Arrays>(r#"{"bar":{"a":[1,2],"b":3}}"#);
+        assert!(foo.is_err());
+    }
+}
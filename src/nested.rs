@@ -0,0 +1,122 @@
+//! Serializes an iterator of iterators into a serde sequence of sequences.
+//!
+//! This is useful for lazily produced two-dimensional data, such as rows of a grid,
+//! without collecting the inner iterators into `Vec`s first.
+//! Both the outer iterator and each inner iterator are cloned when serialized,
+//! so they must implement `Clone`.
+//!
+//! *This module requires the "nested" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize)]
+//! struct Foo<T>
+//! where
+//!     T: IntoIterator + Clone,
+//!     T::Item: IntoIterator<Item = u32> + Clone,
+//! {
+//!     #[serde(with = "serde_iter::nested")]
+//!     bar: T,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: (0..2).map(|row| (0..3).map(move |col| row * 3 + col)),
+//! };
+//! assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+//!     "bar": [[0, 1, 2], [3, 4, 5]]
+//! }));
+//! ```
+
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, Inner, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = Inner> + Clone,
+    Inner: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    for inner in iter {
+        seq.serialize_element(&InnerSeq(inner))?;
+    }
+    seq.end()
+}
+
+struct InnerSeq<Inner>(Inner);
+
+impl<Inner, V> Serialize for InnerSeq<Inner>
+where
+    Inner: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let iter = self.0.clone().into_iter();
+        let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+        for value in iter {
+            seq.serialize_element(&value)?;
+        }
+        seq.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde_json::{json, to_value};
+
+    #[derive(Serialize)]
+    struct Foo<T, Inner>
+    where
+        T: IntoIterator<Item = Inner> + Clone,
+        Inner: IntoIterator<Item = i32> + Clone,
+    {
+        #[serde(with = "super")]
+        bar: T,
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo {
+            bar: Vec::<Vec<i32>>::new(),
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": []
+            })
+        );
+    }
+
+    #[test]
+    fn test_grid() {
+        let grid = [[1, 2], [3, 4]];
+        let value = to_value(Foo {
+            bar: grid.iter().map(|row| row.iter().copied()),
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": [[1, 2], [3, 4]]
+            })
+        );
+    }
+
+    #[test]
+    fn test_empty_rows() {
+        let value = to_value(Foo {
+            bar: vec![vec![], vec![1], vec![]],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": [[], [1], []]
+            })
+        );
+    }
+}
@@ -1,4 +1,8 @@
-use std::cell::Cell;
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+use core::cell::Cell;
+use core::fmt;
+use core::iter::FusedIterator;
 
 /// A hack utility struct to wrap use-once iterators.
 ///
@@ -56,9 +60,24 @@ use std::cell::Cell;
 /// }));
 /// serde_json::to_value(&foo).ok();
 /// ```
-pub struct CloneOnce<T, I>(Cell<Option<I>>)
+///
+/// Use [`CloneOnce::silent`] instead to serialize an empty sequence from the second time on.
+pub struct CloneOnce<T, I>
 where
-    I: IntoIterator<Item = T>;
+    I: IntoIterator<Item = T>,
+{
+    iter: Cell<Option<I>>,
+    /// Whether a consumed value behaves like an empty iterator instead of panicking.
+    silent: bool,
+}
+
+/// A [`CloneOnce`] wrapping a boxed trait object iterator.
+///
+/// This allows storing iterators of different types in the same struct field.
+///
+/// *This type requires the "alloc" feature to be enabled (enabled by default).*
+#[cfg(feature = "alloc")]
+pub type BoxedCloneOnce<T> = CloneOnce<T, Box<dyn Iterator<Item = T>>>;
 
 /// Converts a (non-Clone) iterator into a CloneOnce iterator.
 impl<T, I> From<I> for CloneOnce<T, I>
@@ -66,23 +85,124 @@ where
     I: IntoIterator<Item = T>,
 {
     fn from(iter: I) -> Self {
-        Self(Cell::new(Some(iter)))
+        Self {
+            iter: Cell::new(Some(iter)),
+            silent: false,
+        }
+    }
+}
+
+impl<T, I> CloneOnce<T, I>
+where
+    I: IntoIterator<Item = T>,
+{
+    /// Wraps an iterator that yields nothing instead of panicking when iterated over again.
+    ///
+    /// Unlike [`From`], cloning or iterating over a consumed value is not a bug;
+    /// it behaves like an empty iterator.
+    /// This is useful when the same value may be serialized more than once,
+    /// e.g. by a logger, and only the first output needs to contain the items.
+    ///
+    /// # Example
+    /// ```
+    /// #[derive(serde::Serialize)]
+    /// struct Foo<I>
+    /// where
+    ///     I: IntoIterator<Item = u32> + Clone,
+    /// {
+    ///     #[serde(with = "serde_iter::seq")]
+    ///     bar: I,
+    /// }
+    ///
+    /// let mut v = vec![1, 2, 3];
+    /// let foo = Foo {
+    ///     bar: serde_iter::CloneOnce::silent(v.drain(..)),
+    /// };
+    ///
+    /// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+    ///     "bar": [1, 2, 3]
+    /// }));
+    /// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+    ///     "bar": []
+    /// }));
+    /// ```
+    pub fn silent(iter: I) -> Self {
+        Self {
+            iter: Cell::new(Some(iter)),
+            silent: true,
+        }
+    }
+
+    /// Takes the underlying iterator out, if it has not been moved out by a clone yet.
+    ///
+    /// This allows recovering the iterator if serialization fails before this value is
+    /// serialized, e.g. because a preceding field fails to serialize.
+    /// Once serialization has cloned this value, the iterator has been moved into the clone,
+    /// and this method returns `None`.
+    /// This value becomes empty after calling this method.
+    pub fn take_remaining(&self) -> Option<I> {
+        self.iter.take()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> BoxedCloneOnce<T> {
+    /// Wraps a boxed trait object iterator.
+    ///
+    /// # Example
+    /// ```
+    /// #[derive(serde::Serialize)]
+    /// struct Foo {
+    ///     #[serde(with = "serde_iter::seq")]
+    ///     bar: serde_iter::BoxedCloneOnce<u32>,
+    /// }
+    ///
+    /// let foo = Foo {
+    ///     bar: serde_iter::CloneOnce::from_boxed(Box::new((1..4).map(|x| x * 2))),
+    /// };
+    /// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+    ///     "bar": [2, 4, 6]
+    /// }));
+    /// ```
+    pub fn from_boxed(iter: Box<dyn Iterator<Item = T>>) -> Self {
+        Self::from(iter)
     }
 }
 
 /// Moves the underlying iterator to a cloned value, and leaves a panicking iterator.
+///
+/// For values created with [`CloneOnce::silent`], an empty iterator is left instead.
 impl<T, I> Clone for CloneOnce<T, I>
 where
     I: IntoIterator<Item = T>,
 {
     #[inline]
     fn clone(&self) -> Self {
-        let oi = self.0.take();
-        if oi.is_none() {
+        let oi = self.iter.take();
+        if oi.is_none() && !self.silent {
             panic!("Attempt to clone a CloneOnce twice");
         }
 
-        Self(Cell::new(oi))
+        Self {
+            iter: Cell::new(oi),
+            silent: self.silent,
+        }
+    }
+}
+
+/// Shows whether the underlying iterator has been moved out, without consuming it.
+impl<T, I> fmt::Debug for CloneOnce<T, I>
+where
+    I: IntoIterator<Item = T>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let oi = self.iter.take();
+        let consumed = oi.is_none();
+        self.iter.set(oi);
+
+        f.debug_struct("CloneOnce")
+            .field("consumed", &consumed)
+            .finish()
     }
 }
 
@@ -91,12 +211,186 @@ where
     I: IntoIterator<Item = T>,
 {
     type Item = T;
-    type IntoIter = <I as IntoIterator>::IntoIter;
+    type IntoIter = CloneOnceIter<<I as IntoIterator>::IntoIter>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0
-            .take()
-            .expect("Attempt to iterate over an empty CloneOnce")
-            .into_iter()
+        let oi = self.iter.take();
+        if oi.is_none() && !self.silent {
+            panic!("Attempt to iterate over an empty CloneOnce");
+        }
+
+        CloneOnceIter(oi.map(IntoIterator::into_iter))
+    }
+}
+
+/// The iterator returned by iterating over a [`CloneOnce`].
+///
+/// This is empty if the `CloneOnce` was created with [`CloneOnce::silent`]
+/// and has already been consumed.
+#[derive(Debug)]
+pub struct CloneOnceIter<I>(Option<I>);
+
+impl<I: Iterator> Iterator for CloneOnceIter<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        self.0.as_mut()?.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            Some(iter) => iter.size_hint(),
+            None => (0, Some(0)),
+        }
+    }
+}
+
+impl<I: DoubleEndedIterator> DoubleEndedIterator for CloneOnceIter<I> {
+    fn next_back(&mut self) -> Option<I::Item> {
+        self.0.as_mut()?.next_back()
+    }
+}
+
+impl<I: ExactSizeIterator> ExactSizeIterator for CloneOnceIter<I> {}
+
+impl<I: FusedIterator> FusedIterator for CloneOnceIter<I> {}
+
+#[cfg(test)]
+mod tests {
+    use serde::ser::{Error, Serializer};
+    use serde::Serialize;
+    use serde_json::{json, to_value};
+
+    #[cfg(feature = "alloc")]
+    use super::BoxedCloneOnce;
+    use super::CloneOnce;
+
+    struct Failing;
+
+    impl Serialize for Failing {
+        fn serialize<S: Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+            Err(S::Error::custom("failing"))
+        }
+    }
+
+    #[cfg(feature = "seq")]
+    #[derive(Serialize)]
+    struct Foo<I>
+    where
+        I: IntoIterator<Item = u32> + Clone,
+    {
+        first: Failing,
+        #[serde(with = "crate::seq")]
+        bar: I,
+    }
+
+    #[cfg(feature = "seq")]
+    #[test]
+    fn test_take_remaining_after_failure() {
+        let mut v = vec![1, 2, 3];
+        let foo = Foo {
+            first: Failing,
+            bar: CloneOnce::from(v.drain(..)),
+        };
+        assert!(to_value(&foo).is_err());
+
+        let remaining = foo.bar.take_remaining();
+        let remaining: Vec<u32> = remaining.expect("Iterator was consumed").collect();
+        assert_eq!(remaining, vec![1, 2, 3]);
+        assert!(foo.bar.take_remaining().is_none());
+    }
+
+    #[cfg(feature = "seq")]
+    #[derive(Serialize)]
+    struct Bar<I>
+    where
+        I: IntoIterator<Item = u32> + Clone,
+    {
+        #[serde(with = "crate::seq")]
+        bar: I,
+    }
+
+    #[cfg(feature = "seq")]
+    #[test]
+    fn test_silent_second_serialization() {
+        let mut v = vec![1, 2, 3];
+        let bar = Bar {
+            bar: CloneOnce::silent(v.drain(..)),
+        };
+        let first = to_value(&bar).expect("Failed to serialize");
+        assert_eq!(first, json!({"bar": [1, 2, 3]}));
+        let second = to_value(&bar).expect("Failed to serialize");
+        assert_eq!(second, json!({"bar": []}));
+    }
+
+    #[cfg(feature = "seq")]
+    #[test]
+    #[should_panic(expected = "Attempt to clone a CloneOnce twice")]
+    fn test_from_second_serialization_panics() {
+        let mut v = vec![1, 2, 3];
+        let bar = Bar {
+            bar: CloneOnce::from(v.drain(..)),
+        };
+        let first = to_value(&bar).expect("Failed to serialize");
+        assert_eq!(first, json!({"bar": [1, 2, 3]}));
+        let _ = to_value(&bar);
+    }
+
+    #[cfg(feature = "seq")]
+    #[test]
+    fn test_debug() {
+        let mut v = vec![1, 2, 3];
+        let bar = Bar {
+            bar: CloneOnce::from(v.drain(..)),
+        };
+        assert_eq!(format!("{:?}", bar.bar), "CloneOnce { consumed: false }");
+        assert_eq!(format!("{:?}", bar.bar), "CloneOnce { consumed: false }");
+
+        let value = to_value(&bar).expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": [1, 2, 3]}));
+        assert_eq!(format!("{:?}", bar.bar), "CloneOnce { consumed: true }");
+    }
+
+    #[cfg(feature = "alloc")]
+    #[cfg(feature = "seq")]
+    #[test]
+    fn test_from_boxed() {
+        #[derive(Serialize)]
+        struct Boxed {
+            #[serde(with = "crate::seq")]
+            bar: BoxedCloneOnce<u32>,
+        }
+
+        let evens = Boxed {
+            bar: CloneOnce::from_boxed(Box::new((0..3).map(|x| x * 2))),
+        };
+        let evens_value = to_value(&evens).expect("Failed to serialize");
+        assert_eq!(evens_value, json!({"bar": [0, 2, 4]}));
+
+        let chained = Boxed {
+            bar: CloneOnce::from_boxed(Box::new(vec![5, 6].into_iter().chain(Some(7)))),
+        };
+        let chained_value = to_value(&chained).expect("Failed to serialize");
+        assert_eq!(chained_value, json!({"bar": [5, 6, 7]}));
+    }
+
+    #[test]
+    fn test_iter_traits() {
+        fn assert_fused<I: core::iter::FusedIterator>(_: &I) {}
+
+        let once = CloneOnce::from(vec![1, 2, 3]);
+        let mut iter = once.into_iter();
+        assert_fused(&iter);
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next_back(), Some(3));
+        assert_eq!(iter.collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_take_remaining_after_clone() {
+        let once = CloneOnce::from(vec![1, 2, 3].into_iter());
+        let clone = once.clone();
+        assert!(once.take_remaining().is_none());
+        assert!(clone.take_remaining().is_some());
     }
 }
@@ -0,0 +1,130 @@
+//! Serializes an iterator of serializables into a serde sequence of labeled pages.
+//!
+//! The elements are split into pages of `per_page` elements, the last page possibly shorter,
+//! and each page is emitted as `{"page": index, "items": [...]}`, numbering pages from 0.
+//! This embeds a paginated view in a single document.
+//! An empty iterator produces no pages.
+//!
+//! The elements of each page are buffered before the page is serialized.
+//! Serialization fails if `per_page` is 0.
+//!
+//! To avoid writing a `serialize_with` function,
+//! wrap the iterator in [`Pages`](struct.Pages.html) together with the page size.
+//!
+//! *This module requires the "pages" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! let pages = serde_iter::pages::Pages::new(1..=3, 2);
+//! assert_eq!(serde_json::to_value(&pages).unwrap(), serde_json::json!([
+//!     {"page": 0, "items": [1, 2]},
+//!     {"page": 1, "items": [3]},
+//! ]));
+//! ```
+
+use serde::ser::{Error, Serialize, SerializeSeq, SerializeStruct, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, V>(iter: &T, per_page: usize, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    if per_page == 0 {
+        return Err(S::Error::custom("page size must be positive"));
+    }
+
+    let mut iter = iter.clone().into_iter();
+    let mut seq = serializer.serialize_seq(None)?;
+    for page in 0.. {
+        let items: Vec<V> = iter.by_ref().take(per_page).collect();
+        if items.is_empty() {
+            break;
+        }
+        seq.serialize_element(&Page { page, items })?;
+    }
+    seq.end()
+}
+
+/// Wraps an iterator so that it serializes as a sequence of labeled pages.
+///
+/// Refer to the [module-level documentation](index.html).
+#[derive(Debug, Clone)]
+pub struct Pages<I> {
+    iter: I,
+    per_page: usize,
+}
+
+impl<I> Pages<I> {
+    /// Creates a sequence of pages of `per_page` elements from `iter`.
+    pub fn new(iter: I, per_page: usize) -> Self {
+        Self { iter, per_page }
+    }
+}
+
+impl<I, V> Serialize for Pages<I>
+where
+    I: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(&self.iter, self.per_page, serializer)
+    }
+}
+
+struct Page<V> {
+    page: usize,
+    items: Vec<V>,
+}
+
+impl<V> Serialize for Page<V>
+where
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut st = serializer.serialize_struct("Page", 2)?;
+        st.serialize_field("page", &self.page)?;
+        st.serialize_field("items", &self.items)?;
+        st.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, to_value};
+
+    use super::Pages;
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Pages::new(0..0, 2));
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!([]));
+    }
+
+    #[test]
+    fn test_pages() {
+        let value = to_value(Pages::new(0..5, 2));
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!([
+                {"page": 0, "items": [0, 1]},
+                {"page": 1, "items": [2, 3]},
+                {"page": 2, "items": [4]}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_zero_per_page() {
+        assert!(to_value(Pages::new(0..5, 0)).is_err());
+    }
+}
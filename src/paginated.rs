@@ -0,0 +1,165 @@
+//! Serializes one page of an iterator of serializables in a pagination envelope.
+//!
+//! The output is `{"page": p, "per_page": n, "total": total, "items": [...]}`,
+//! where `items` contains only the elements of the requested page
+//! and `total` is the number of elements in the whole iterator.
+//! Pages are numbered from 1.
+//!
+//! The iterator is cloned twice: once to count the total and once to serialize the page.
+//! Serialization fails if `page` is 0.
+//!
+//! A field can also store a [`Paginated`](struct.Paginated.html) value,
+//! which remembers the page number and page size itself.
+//!
+//! *This module requires the "paginated" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! let paginated = serde_iter::paginated::Paginated::new(1..=5, 2, 2);
+//! assert_eq!(serde_json::to_value(&paginated).unwrap(), serde_json::json!({
+//!     "page": 2,
+//!     "per_page": 2,
+//!     "total": 5,
+//!     "items": [3, 4],
+//! }));
+//! ```
+
+use serde::ser::{Error, Serialize, SerializeSeq, SerializeStruct, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, V>(
+    iter: &T,
+    page: usize,
+    per_page: usize,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    let skip = page
+        .checked_sub(1)
+        .ok_or_else(|| S::Error::custom("page numbers start at 1"))?
+        .saturating_mul(per_page);
+    let total = iter.clone().into_iter().count();
+
+    let mut st = serializer.serialize_struct("Paginated", 4)?;
+    st.serialize_field("page", &page)?;
+    st.serialize_field("per_page", &per_page)?;
+    st.serialize_field("total", &total)?;
+    st.serialize_field(
+        "items",
+        &Items {
+            iter,
+            skip,
+            take: per_page,
+        },
+    )?;
+    st.end()
+}
+
+/// Wraps an iterator so that it serializes as one page in a pagination envelope.
+///
+/// Refer to the [module-level documentation](index.html).
+#[derive(Debug, Clone)]
+pub struct Paginated<I> {
+    iter: I,
+    page: usize,
+    per_page: usize,
+}
+
+impl<I> Paginated<I> {
+    /// Creates an envelope for page `page` of `iter`, with `per_page` elements per page.
+    pub fn new(iter: I, page: usize, per_page: usize) -> Self {
+        Self {
+            iter,
+            page,
+            per_page,
+        }
+    }
+}
+
+impl<I, V> Serialize for Paginated<I>
+where
+    I: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(&self.iter, self.page, self.per_page, serializer)
+    }
+}
+
+struct Items<'a, T> {
+    iter: &'a T,
+    skip: usize,
+    take: usize,
+}
+
+impl<'a, T, V> Serialize for Items<'a, T>
+where
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let iter = self
+            .iter
+            .clone()
+            .into_iter()
+            .skip(self.skip)
+            .take(self.take);
+        let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+        for value in iter {
+            seq.serialize_element(&value)?;
+        }
+        seq.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, to_value};
+
+    use super::Paginated;
+
+    #[test]
+    fn test_page() {
+        let value = to_value(Paginated::new(0..25, 2, 10));
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "page": 2,
+                "per_page": 10,
+                "total": 25,
+                "items": [10, 11, 12, 13, 14, 15, 16, 17, 18, 19]
+            })
+        );
+    }
+
+    #[test]
+    fn test_last_page() {
+        let value = to_value(Paginated::new(0..25, 3, 10));
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "page": 3,
+                "per_page": 10,
+                "total": 25,
+                "items": [20, 21, 22, 23, 24]
+            })
+        );
+    }
+
+    #[test]
+    fn test_page_zero() {
+        assert!(to_value(Paginated::new(0..25, 0, 10)).is_err());
+    }
+}
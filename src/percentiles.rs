@@ -0,0 +1,202 @@
+//! Serializes an iterator of numbers into a map of percentiles.
+//!
+//! The iterator is collected and sorted,
+//! and the output is a map from `"p{n}"` to the `n`-th percentile,
+//! e.g. `{"p50": 50, "p90": 90, "p99": 99}`.
+//! Percentiles are computed with the nearest-rank method,
+//! so every reported value is an element of the data.
+//! If the iterator is empty, every percentile is `null`.
+//!
+//! The `serialize` function reports the 50th, 90th and 99th percentiles.
+//! Use [`serialize_percentiles`](fn.serialize_percentiles.html) to select other percentiles.
+//!
+//! Serialization fails if the data contains values that are not comparable, e.g. `NaN`.
+//!
+//! *This module requires the "percentiles" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::percentiles")]
+//!     bar: std::ops::RangeInclusive<i64>,
+//! }
+//!
+//! let foo = Foo { bar: 1..=100 };
+//! assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+//!     "bar": {"p50": 50, "p90": 90, "p99": 99}
+//! }));
+//! ```
+
+use std::cell::Cell;
+use std::cmp::Ordering;
+
+use serde::ser::{Error, Serialize, SerializeMap, Serializer};
+
+const DEFAULT_PERCENTILES: &[u8] = &[50, 90, 99];
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize + PartialOrd,
+{
+    serialize_percentiles(iter, DEFAULT_PERCENTILES, serializer)
+}
+
+/// Serializes the given percentiles of the iterator.
+///
+/// Returns an error if a percentile is greater than 100,
+/// before anything is passed to the serializer.
+pub fn serialize_percentiles<S, T, V>(
+    iter: &T,
+    percentiles: &[u8],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize + PartialOrd,
+{
+    if let Some(percentile) = percentiles.iter().find(|&&percentile| percentile > 100) {
+        return Err(S::Error::custom(format_args!(
+            "percentile {} is greater than 100",
+            percentile
+        )));
+    }
+
+    let mut values: Vec<V> = iter.clone().into_iter().collect();
+    // values like `NaN` are not even comparable to themselves,
+    // and must be rejected before sorting since the comparator would not be a total order
+    if values
+        .iter()
+        .any(|value| value.partial_cmp(value).is_none())
+    {
+        return Err(S::Error::custom("values are not comparable"));
+    }
+    let incomparable = Cell::new(false);
+    values.sort_by(|a, b| {
+        a.partial_cmp(b).unwrap_or_else(|| {
+            incomparable.set(true);
+            Ordering::Equal
+        })
+    });
+    if incomparable.get() {
+        return Err(S::Error::custom("values are not comparable"));
+    }
+
+    let mut map = serializer.serialize_map(Some(percentiles.len()))?;
+    for &percentile in percentiles {
+        // nearest rank: the smallest value with at least `percentile`% of the data at or below it
+        let rank = (usize::from(percentile) * values.len()).div_ceil(100);
+        let value = values.get(rank.saturating_sub(1));
+        map.serialize_entry(&format_args!("p{}", percentile), &value)?;
+    }
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_value};
+
+    #[derive(Serialize)]
+    struct Foo<T>
+    where
+        T: IntoIterator<Item = f64> + Clone,
+    {
+        #[serde(with = "super")]
+        bar: T,
+    }
+
+    fn quartiles<S: Serializer>(iter: &[i64; 4], serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize_percentiles(iter, &[0, 25, 50, 75, 100], serializer)
+    }
+
+    fn invalid<S: Serializer>(iter: &[i64; 4], serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize_percentiles(iter, &[101], serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Quartiles {
+        #[serde(serialize_with = "quartiles")]
+        bar: [i64; 4],
+    }
+
+    #[derive(Serialize)]
+    struct Invalid {
+        #[serde(serialize_with = "invalid")]
+        bar: [i64; 4],
+    }
+
+    #[test]
+    fn test_p50() {
+        let value = to_value(Foo {
+            bar: (1..=100).rev().map(f64::from),
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"p50": 50.0, "p90": 90.0, "p99": 99.0}
+            })
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo { bar: vec![] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"p50": null, "p90": null, "p99": null}
+            })
+        );
+    }
+
+    #[test]
+    fn test_custom_percentiles() {
+        let value = to_value(Quartiles { bar: [4, 1, 3, 2] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"p0": 1, "p25": 1, "p50": 2, "p75": 3, "p100": 4}
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_percentile() {
+        assert!(to_value(Invalid { bar: [4, 1, 3, 2] }).is_err());
+    }
+
+    #[test]
+    fn test_invalid_percentile_writes_nothing() {
+        fn late_invalid<S: Serializer>(iter: &[i64; 4], serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize_percentiles(iter, &[50, 101], serializer)
+        }
+
+        #[derive(Serialize)]
+        struct LateInvalid {
+            #[serde(serialize_with = "late_invalid")]
+            bar: [i64; 4],
+        }
+
+        let mut written = Vec::new();
+        let result = serde_json::to_writer(&mut written, &LateInvalid { bar: [4, 1, 3, 2] });
+        assert!(result.is_err());
+        assert_eq!(written, br#"{"bar":"#);
+    }
+
+    #[test]
+    fn test_nan() {
+        let value = to_value(Foo {
+            bar: vec![1.0, f64::NAN, 2.0],
+        });
+        let err = value.expect_err("NaN should fail");
+        assert!(err.to_string().contains("not comparable"));
+    }
+}
@@ -0,0 +1,130 @@
+//! Serializes an iterator of records into a serde sequence,
+//! keeping only the selected fields of each record.
+//!
+//! This is useful for API field selection, where the fields are chosen at runtime.
+//!
+//! Each record is first serialized into a `serde_json::Value`,
+//! which must be an object, and then only the fields named in the list are emitted,
+//! in the order of the list.
+//! Fields in the list that a record does not have are skipped.
+//!
+//! *This module requires the "json" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize, Clone)]
+//! struct Record {
+//!     id: u32,
+//!     name: &'static str,
+//! }
+//!
+//! let records = vec![Record { id: 1, name: "foo" }, Record { id: 2, name: "bar" }];
+//!
+//! let mut buf = Vec::new();
+//! serde_iter::project::serialize(
+//!     &records,
+//!     &["id"],
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), r#"[{"id":1},{"id":2}]"#);
+//! ```
+
+use serde::ser::{Error, Serialize, SerializeMap, SerializeSeq, Serializer};
+use serde_json::{Map, Value};
+
+/// Refer to the [module-level documentation](index.html).
+///
+/// Returns an error if a record cannot be converted into a `serde_json::Value`,
+/// or if it is not converted into an object.
+pub fn serialize<S, T, R>(iter: &T, fields: &[&str], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = R> + Clone,
+    R: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    for record in iter {
+        let record = match serde_json::to_value(&record).map_err(S::Error::custom)? {
+            Value::Object(object) => object,
+            _ => return Err(S::Error::custom("projected record is not an object")),
+        };
+        seq.serialize_element(&Projection {
+            object: &record,
+            fields,
+        })?;
+    }
+    seq.end()
+}
+
+struct Projection<'a> {
+    object: &'a Map<String, Value>,
+    fields: &'a [&'a str],
+}
+
+impl<'a> Serialize for Projection<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        for &field in self.fields {
+            if let Some(value) = self.object.get(field) {
+                map.serialize_entry(field, value)?;
+            }
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_value};
+
+    #[derive(Serialize, Clone)]
+    struct Record {
+        id: u32,
+        name: &'static str,
+    }
+
+    fn ids<S: Serializer>(iter: &[Record; 2], serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, &["id"], serializer)
+    }
+
+    fn ids_of_numbers<S: Serializer>(iter: &[i32; 2], serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, &["id"], serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(serialize_with = "ids")]
+        bar: [Record; 2],
+    }
+
+    #[derive(Serialize)]
+    struct Numbers {
+        #[serde(serialize_with = "ids_of_numbers")]
+        bar: [i32; 2],
+    }
+
+    #[test]
+    fn test_project() {
+        let value = to_value(Foo {
+            bar: [Record { id: 1, name: "a" }, Record { id: 2, name: "b" }],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": [{"id": 1}, {"id": 2}]
+            })
+        );
+    }
+
+    #[test]
+    fn test_not_object() {
+        assert!(to_value(Numbers { bar: [1, 2] }).is_err());
+    }
+}
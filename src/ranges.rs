@@ -0,0 +1,228 @@
+//! Serializes a strictly ascending iterator of `i64` into a serde sequence of inclusive ranges.
+//!
+//! Each run of consecutive integers is collapsed into a `[start, end]` pair,
+//! so `[1, 2, 3, 5, 6, 10]` is encoded as `[[1, 3], [5, 6], [10, 10]]`.
+//! This is compact for sorted integer sets with long runs, such as ID lists.
+//!
+//! Serialization fails if an element is not greater than the previous one.
+//! The `deserialize` function expands the ranges back into any `FromIterator<i64>` container,
+//! and fails if a range is reversed or does not start after the previous range.
+//! Since a short input can describe a huge number of integers,
+//! `deserialize` fails if the ranges contain more than [`MAX_LEN`] integers in total.
+//! The ranges are expanded lazily while the container is built,
+//! without an intermediate buffer of the integers.
+//!
+//! *This module requires the "ranges" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::ranges")]
+//!     bar: Vec<i64>,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: vec![1, 2, 3, 5, 6, 10],
+//! };
+//! let value = serde_json::to_value(&foo).unwrap();
+//! assert_eq!(value, serde_json::json!({
+//!     "bar": [[1, 3], [5, 6], [10, 10]]
+//! }));
+//!
+//! let foo: Foo = serde_json::from_value(value).unwrap();
+//! assert_eq!(foo.bar, vec![1, 2, 3, 5, 6, 10]);
+//! ```
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::{self, SerializeSeq, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+///
+/// Returns an error if the iterator is not strictly ascending.
+pub fn serialize<S, T>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = i64> + Clone,
+{
+    let mut seq = serializer.serialize_seq(None)?;
+    let mut run: Option<(i64, i64)> = None;
+    for value in iter.clone() {
+        run = match run {
+            None => Some((value, value)),
+            Some((_, end)) if value <= end => {
+                return Err(<S::Error as ser::Error>::custom(format_args!(
+                    "expected strictly ascending integers, got {} after {}",
+                    value, end
+                )));
+            }
+            Some((start, end)) if Some(value) == end.checked_add(1) => Some((start, value)),
+            Some(range) => {
+                seq.serialize_element(&range)?;
+                Some((value, value))
+            }
+        };
+    }
+    if let Some(range) = run {
+        seq.serialize_element(&range)?;
+    }
+    seq.end()
+}
+
+/// The maximum total number of integers accepted by [`deserialize`].
+pub const MAX_LEN: usize = 1 << 24;
+
+/// Refer to the [module-level documentation](index.html).
+///
+/// Returns an error if a range is reversed or does not start after the previous range,
+/// or if the ranges contain more than [`MAX_LEN`] integers in total.
+pub fn deserialize<'de, D, C>(deserializer: D) -> Result<C, D::Error>
+where
+    D: Deserializer<'de>,
+    C: FromIterator<i64>,
+{
+    deserializer.deserialize_seq(RangesVisitor(PhantomData))
+}
+
+struct RangesVisitor<C>(PhantomData<C>);
+
+impl<'de, C> Visitor<'de> for RangesVisitor<C>
+where
+    C: FromIterator<i64>,
+{
+    type Value = C;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence of ascending [start, end] integer ranges")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<C, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut ranges = Vec::new();
+        let mut prev_end: Option<i64> = None;
+        let mut len: i128 = 0;
+        while let Some((start, end)) = seq.next_element::<(i64, i64)>()? {
+            if start > end {
+                return Err(<A::Error as de::Error>::custom(format_args!(
+                    "range [{}, {}] is reversed",
+                    start, end
+                )));
+            }
+            if let Some(prev_end) = prev_end.filter(|&prev_end| start <= prev_end) {
+                return Err(<A::Error as de::Error>::custom(format_args!(
+                    "range starting at {} overlaps the range ending at {}",
+                    start, prev_end
+                )));
+            }
+            len += i128::from(end) - i128::from(start) + 1;
+            if usize::try_from(len).map_or(true, |len| len > MAX_LEN) {
+                return Err(<A::Error as de::Error>::custom(format_args!(
+                    "ranges contain more than the maximum of {} integers",
+                    MAX_LEN
+                )));
+            }
+            ranges.push(start..=end);
+            prev_end = Some(end);
+        }
+        Ok(ranges.into_iter().flatten().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+    use std::iter::FromIterator;
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::{from_value, json, to_value};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Foo {
+        #[serde(with = "super")]
+        bar: Vec<i64>,
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo { bar: vec![] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": []
+            })
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let bar = vec![-2, -1, 0, 4, 7, 8, 9, i64::MAX];
+        let value = to_value(Foo { bar: bar.clone() });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": [[-2, 0], [4, 4], [7, 9], [i64::MAX, i64::MAX]]
+            })
+        );
+
+        let foo: Foo = from_value(value).expect("Failed to deserialize");
+        assert_eq!(foo, Foo { bar });
+    }
+
+    #[test]
+    fn test_unsorted() {
+        assert!(to_value(Foo { bar: vec![1, 3, 2] }).is_err());
+        assert!(to_value(Foo { bar: vec![1, 1] }).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_invalid() {
+        assert!(from_value::<Foo>(json!({"bar": [[3, 1]]})).is_err());
+        assert!(from_value::<Foo>(json!({"bar": [[1, 3], [3, 4]]})).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_lazy() {
+        /// Keeps only the first three elements.
+        #[derive(Debug, PartialEq)]
+        struct Head(Vec<i64>);
+
+        impl FromIterator<i64> for Head {
+            fn from_iter<I: IntoIterator<Item = i64>>(iter: I) -> Self {
+                Self(iter.into_iter().take(3).collect())
+            }
+        }
+
+        #[derive(Deserialize)]
+        struct Bar {
+            #[serde(with = "super")]
+            bar: Head,
+        }
+
+        let max_len = i64::try_from(super::MAX_LEN).expect("MAX_LEN fits in i64");
+        let bar = from_value::<Bar>(json!({"bar": [[10, 9 + max_len]]}));
+        let bar = bar.expect("Failed to deserialize");
+        assert_eq!(bar.bar, Head(vec![10, 11, 12]));
+    }
+
+    #[test]
+    fn test_deserialize_too_long() {
+        let huge = from_value::<Foo>(json!({"bar": [[0, i64::MAX]]}));
+        assert!(huge.is_err());
+
+        let full = from_value::<Foo>(json!({"bar": [[i64::MIN, i64::MAX]]}));
+        assert!(full.is_err());
+
+        let max_len = i64::try_from(super::MAX_LEN).expect("MAX_LEN fits in i64");
+        let split = from_value::<Foo>(json!({"bar": [[0, 1], [3, 1 + max_len]]}));
+        assert!(split.is_err());
+    }
+}
@@ -0,0 +1,246 @@
+use alloc::{rc::Rc, vec::Vec};
+use core::cell::RefCell;
+use core::fmt;
+use core::iter::{Fuse, FusedIterator};
+
+/// An iterator wrapper that caches the yielded items for replaying.
+///
+/// On the first iteration, the items are pulled from the underlying iterator
+/// and a clone of each item is stored in a cache shared by all clones of this value.
+/// Subsequent iterations replay the cached items
+/// instead of pulling them from the underlying iterator again,
+/// which is useful if computing the items is expensive.
+/// If an iteration stops early, e.g. because serialization fails,
+/// the next iteration replays the cached items and resumes the underlying iterator.
+/// The underlying iterator is fused,
+/// so it is never polled again after it returns `None` for the first time.
+///
+/// Unlike [`CloneOnce`](struct.CloneOnce.html), this value can be serialized any number of times,
+/// at the cost of keeping all items in memory.
+///
+/// *This struct requires the "reusable" feature to be enabled.*
+///
+/// # Example
+/// ```
+/// use std::cell::Cell;
+///
+/// #[derive(serde::Serialize)]
+/// struct Foo<I>
+/// where
+///     I: IntoIterator<Item = u32> + Clone,
+/// {
+///     #[serde(with = "serde_iter::seq")]
+///     bar: I,
+/// }
+///
+/// let computed = Cell::new(0);
+/// let foo = Foo {
+///     bar: serde_iter::ReusableIter::new((1..4).map(|x| {
+///         computed.set(computed.get() + 1);
+///         x * x
+///     })),
+/// };
+///
+/// for _ in 0..2 {
+///     assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///         "bar": [1, 4, 9]
+///     }));
+/// }
+/// assert_eq!(computed.get(), 3);
+/// ```
+pub struct ReusableIter<I>(Rc<RefCell<Cache<I>>>)
+where
+    I: Iterator;
+
+struct Cache<I>
+where
+    I: Iterator,
+{
+    source: Fuse<I>,
+    items: Vec<I::Item>,
+}
+
+impl<I> ReusableIter<I>
+where
+    I: Iterator,
+{
+    /// Wraps an iterator, caching its items on the first iteration.
+    pub fn new(iter: I) -> Self {
+        Self(Rc::new(RefCell::new(Cache {
+            source: iter.fuse(),
+            items: Vec::new(),
+        })))
+    }
+}
+
+/// Shares the cache with the cloned value.
+impl<I> Clone for ReusableIter<I>
+where
+    I: Iterator,
+{
+    fn clone(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
+
+/// Shows the number of cached items.
+impl<I> fmt::Debug for ReusableIter<I>
+where
+    I: Iterator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("ReusableIter");
+        match self.0.try_borrow() {
+            Ok(cache) => debug.field("cached", &cache.items.len()),
+            Err(_) => debug.field("cached", &format_args!("<borrowed>")),
+        };
+        debug.finish()
+    }
+}
+
+impl<I> IntoIterator for ReusableIter<I>
+where
+    I: Iterator,
+    I::Item: Clone,
+{
+    type Item = I::Item;
+    type IntoIter = Replay<I>;
+
+    fn into_iter(self) -> Replay<I> {
+        Replay {
+            cache: self.0,
+            index: 0,
+        }
+    }
+}
+
+/// The iterator returned by iterating over a [`ReusableIter`].
+///
+/// This yields the cached items first, then pulls and caches the remaining items
+/// from the underlying iterator.
+pub struct Replay<I>
+where
+    I: Iterator,
+{
+    cache: Rc<RefCell<Cache<I>>>,
+    index: usize,
+}
+
+impl<I> Iterator for Replay<I>
+where
+    I: Iterator,
+    I::Item: Clone,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let mut cache = self.cache.borrow_mut();
+        let item = match cache.items.get(self.index) {
+            Some(item) => item.clone(),
+            None => {
+                let item = cache.source.next()?;
+                cache.items.push(item.clone());
+                item
+            }
+        };
+        self.index += 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let cache = self.cache.borrow();
+        let cached = cache.items.len().saturating_sub(self.index);
+        let (lower, upper) = cache.source.size_hint();
+        (
+            lower.saturating_add(cached),
+            upper.and_then(|upper| upper.checked_add(cached)),
+        )
+    }
+}
+
+impl<I> FusedIterator for Replay<I>
+where
+    I: Iterator,
+    I::Item: Clone,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use serde::Serialize;
+    use serde_json::{json, to_value};
+
+    use super::ReusableIter;
+
+    #[cfg(feature = "seq")]
+    #[derive(Serialize)]
+    struct Foo<I>
+    where
+        I: IntoIterator<Item = u32> + Clone,
+    {
+        #[serde(with = "crate::seq")]
+        bar: I,
+    }
+
+    #[cfg(feature = "seq")]
+    #[test]
+    fn test_polled_once() {
+        let polls = Cell::new(0);
+        let foo = Foo {
+            bar: ReusableIter::new((1..4).map(|x| {
+                polls.set(polls.get() + 1);
+                x * 10
+            })),
+        };
+
+        let first = to_value(&foo).expect("Failed to serialize");
+        assert_eq!(first, json!({"bar": [10, 20, 30]}));
+        assert_eq!(polls.get(), 3);
+
+        let second = to_value(&foo).expect("Failed to serialize");
+        assert_eq!(second, first);
+        assert_eq!(polls.get(), 3);
+    }
+
+    #[test]
+    fn test_resume_after_partial() {
+        let polls = Cell::new(0);
+        let reusable = ReusableIter::new((1..4).inspect(|_| polls.set(polls.get() + 1)));
+
+        let partial: Vec<u32> = reusable.clone().into_iter().take(2).collect();
+        assert_eq!(partial, vec![1, 2]);
+        assert_eq!(polls.get(), 2);
+
+        let iter = reusable.clone().into_iter();
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+        let full: Vec<u32> = iter.collect();
+        assert_eq!(full, vec![1, 2, 3]);
+        assert_eq!(polls.get(), 3);
+        assert_eq!(format!("{:?}", reusable), "ReusableIter { cached: 3 }");
+    }
+
+    #[test]
+    fn test_fused_source() {
+        /// Yields `None` on every other call.
+        struct Flaky(u32);
+
+        impl Iterator for Flaky {
+            type Item = u32;
+
+            fn next(&mut self) -> Option<u32> {
+                self.0 += 1;
+                Some(self.0).filter(|count| !count.is_multiple_of(2))
+            }
+        }
+
+        let reusable = ReusableIter::new(Flaky(1));
+        let mut iter = reusable.clone().into_iter();
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+
+        let replayed: Vec<u32> = reusable.into_iter().collect();
+        assert!(replayed.is_empty());
+    }
+}
@@ -5,6 +5,7 @@
 //! # Usage
 //! To derive `Serialize` for a struct that contains arbitrary `Iterator` types,
 //! simply add `#[serde(with = "serde_iter::seq")]` on the fields using such types.
+//! To serialize an iterator elsewhere, e.g. as an element of a `Vec`, wrap it in [`AsSeq`].
 //!
 //! # Example
 //! ```
@@ -22,6 +23,14 @@
 //! }));
 //! ```
 //!
+//! # Length hint
+//! `serialize` passes the lower bound of the iterator's size hint to the serializer.
+//! Computing the size hint of long adapter chains is not free,
+//! so if the value is only serialized into formats that ignore the length, such as JSON,
+//! [`serialize_hint`](fn.serialize_hint.html) with `SeqHint::None`
+//! or [`serialize_lazy`](fn.serialize_lazy.html) can be used to pass `None` without computing it.
+//! Length-prefixed formats such as bincode reject `None`.
+//!
 //! # Cloning
 //! Since serialization could be called multiple times on the same value,
 //! each time the iterator is serialized, `serde_iter` would clone the iterator and only consume
@@ -54,6 +63,7 @@
 //! relatively cheap.
 //! On the other hand, `String::clone()` allocates a new buffer on the heap and copies all
 //! characters to the new buffer, which is not low-cost, and it is better to borrow the strings.
+//! [`serialize_cheap`](fn.serialize_cheap.html) enforces this by only accepting `Copy` elements.
 //!
 //! Consider this example:
 //!
@@ -144,7 +154,21 @@
 //! to prevent cloning unnecessarily, it might be desirable to
 //! store the mapped data in a `Vec` beforehand.
 
-use serde::ser::{Serialize, SerializeSeq, Serializer};
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec::Vec};
+use core::cell::{Cell, RefCell};
+use core::fmt::Display;
+use core::iter::Peekable;
+#[cfg(feature = "alloc")]
+use core::{fmt, iter::FromIterator, marker::PhantomData};
+
+#[cfg(feature = "alloc")]
+use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Error, Serialize, SerializeSeq, Serializer};
+
+pub mod options;
+
+use options::SeqHint;
 
 /// Refer to the [module-level documentation](index.html).
 pub fn serialize<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
@@ -161,12 +185,1004 @@ where
     seq.end()
 }
 
+/// A wrapper that serializes an iterator into a serde sequence.
+///
+/// This is equivalent to [`serialize`](fn.serialize.html),
+/// but can be used as a value anywhere a `Serialize` type is expected,
+/// e.g. inside a `Vec` or a tuple, or passed directly to a serializer,
+/// without `#[serde(with)]`.
+///
+/// # Example
+/// ```
+/// let value = serde_json::to_value(serde_iter::seq::AsSeq((1..4).map(|x| x * 2))).unwrap();
+/// assert_eq!(value, serde_json::json!([2, 4, 6]));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AsSeq<T>(pub T);
+
+impl<T, V> Serialize for AsSeq<T>
+where
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(&self.0, serializer)
+    }
+}
+
+/// Serializes the elements of an iterator into a serde sequence
+/// until their cumulative weight would exceed a budget.
+///
+/// The weight of each element is computed with the weight function,
+/// and serialization stops before the first element
+/// that would bring the total weight above the budget,
+/// even if later elements are lighter.
+/// This is useful for bounding the output when elements have variable sizes or costs.
+///
+/// # Example
+/// ```
+/// let take = serde_iter::seq::WeightedTake::new(
+///     vec!["a", "bbb", "cc", "d"],
+///     5,
+///     |value: &&str| value.len() as u64,
+/// );
+/// assert_eq!(
+///     serde_json::to_value(&take).unwrap(),
+///     serde_json::json!(["a", "bbb"]),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct WeightedTake<I, F> {
+    iter: I,
+    budget: u64,
+    weight_fn: F,
+}
+
+impl<I, F> WeightedTake<I, F> {
+    /// Creates a sequence of the elements of `iter` whose cumulative weight fits into `budget`.
+    pub fn new(iter: I, budget: u64, weight_fn: F) -> Self {
+        Self {
+            iter,
+            budget,
+            weight_fn,
+        }
+    }
+}
+
+impl<I, F, V> Serialize for WeightedTake<I, F>
+where
+    I: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+    F: Fn(&V) -> u64,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(None)?;
+        let mut total: u64 = 0;
+        for value in self.iter.clone() {
+            match total.checked_add((self.weight_fn)(&value)) {
+                Some(sum) if sum <= self.budget => total = sum,
+                _ => break,
+            }
+            seq.serialize_element(&value)?;
+        }
+        seq.end()
+    }
+}
+
+/// Serializes an iterator stored in a `Cell` into a serde sequence, consuming it exactly once.
+///
+/// Unlike [`serialize`](fn.serialize.html), this does not require the iterator to be `Clone`.
+/// The iterator is taken out of the `Cell` and drained,
+/// leaving the `Cell` empty afterwards.
+/// Calling this function again on the same `Cell` therefore yields an empty sequence,
+/// so it should only be used where the value is known to be serialized once.
+/// This is similar to [`CloneOnce`](../struct.CloneOnce.html),
+/// except that a second serialization is empty instead of panicking.
+///
+/// Serializers that make two passes over the value also see an empty sequence.
+/// For example, `bincode::serialize` computes the output size in a first pass,
+/// which consumes the iterator, so the actual output contains an empty sequence.
+/// Use a single-pass serializer such as `bincode::serialize_into` instead.
+///
+/// # Example
+/// ```
+/// use std::cell::Cell;
+///
+/// #[derive(serde::Serialize)]
+/// struct Foo<I: Iterator<Item = u32>> {
+///     #[serde(serialize_with = "serde_iter::seq::serialize_ref")]
+///     bar: Cell<Option<I>>,
+/// }
+///
+/// let mut v = vec![1, 2, 3];
+/// let foo = Foo {
+///     bar: Cell::new(Some(v.drain(..))),
+/// };
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": [1, 2, 3]
+/// }));
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": []
+/// }));
+/// ```
+pub fn serialize_ref<S, I, V>(iter: &Cell<Option<I>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    I: IntoIterator<Item = V>,
+    V: Serialize,
+{
+    let iter = match iter.take() {
+        Some(iter) => iter.into_iter(),
+        None => return serializer.serialize_seq(Some(0))?.end(),
+    };
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    for value in iter {
+        seq.serialize_element(&value)?;
+    }
+    seq.end()
+}
+
+/// Serializes an exact-size iterator into a serde sequence with its exact length.
+///
+/// [`serialize`](fn.serialize.html) passes the lower bound of the size hint to the serializer,
+/// which underestimates the length of adapters like `Filter`.
+/// Formats that write a length prefix, such as bincode or MessagePack,
+/// produce corrupt output if the length is wrong.
+/// This function requires an `ExactSizeIterator` and passes its `len()` instead.
+///
+/// Iterators with unknown lengths, such as `Filter`, are rejected at compile time:
+///
+/// ```compile_fail
+/// #[derive(serde::Serialize)]
+/// struct Foo<I: Iterator<Item = i32> + Clone> {
+///     #[serde(serialize_with = "serde_iter::seq::serialize_exact")]
+///     bar: I,
+/// }
+///
+/// let v = vec![1, 2, 3];
+/// serde_json::to_value(&Foo { bar: v.into_iter().filter(|x| x % 2 == 1) }).unwrap();
+/// ```
+///
+/// To serialize such iterators into length-prefixed formats, collect them into a `Vec` beforehand.
+pub fn serialize_exact<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    T::IntoIter: ExactSizeIterator,
+    V: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut seq = serializer.serialize_seq(Some(iter.len()))?;
+    for value in iter {
+        seq.serialize_element(&value)?;
+    }
+    seq.end()
+}
+
+/// Deserializes a serde sequence into any `FromIterator` container.
+///
+/// This allows `#[serde(with = "serde_iter::seq")]` on fields that are also deserialized,
+/// such as a `Vec` field that is serialized from an iterator elsewhere.
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Foo {
+///     #[serde(with = "serde_iter::seq")]
+///     bar: Vec<i32>,
+/// }
+///
+/// let foo: Foo = serde_json::from_str(r#"{"bar":[1,2,3]}"#).unwrap();
+/// assert_eq!(foo.bar, vec![1, 2, 3]);
+/// ```
+///
+/// *This function requires the "alloc" feature to be enabled (enabled by default).*
+#[cfg(feature = "alloc")]
+pub fn deserialize<'de, D, C, V>(deserializer: D) -> Result<C, D::Error>
+where
+    D: Deserializer<'de>,
+    C: FromIterator<V>,
+    V: Deserialize<'de>,
+{
+    deserializer.deserialize_seq(SeqVisitor(PhantomData))
+}
+
+#[cfg(feature = "alloc")]
+struct SeqVisitor<C, V>(PhantomData<(C, V)>);
+
+#[cfg(feature = "alloc")]
+impl<'de, C, V> Visitor<'de> for SeqVisitor<C, V>
+where
+    C: FromIterator<V>,
+    V: Deserialize<'de>,
+{
+    type Value = C;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<C, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut values = Vec::with_capacity(crate::size_hint::cautious::<V>(seq.size_hint()));
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(values.into_iter().collect())
+    }
+}
+
+/// Serializes an iterator into a serde sequence, projecting each element with a function.
+///
+/// This is equivalent to serializing `iter.map(f)`,
+/// but does not require the mapped iterator, and hence the closure, to be `Clone`,
+/// and avoids collecting the projected elements into a `Vec` beforehand.
+///
+/// # Example
+/// ```
+/// let mut buf = Vec::new();
+/// serde_iter::seq::serialize_with(
+///     &vec!["a", "bcd"],
+///     |value: &str| value.len(),
+///     &mut serde_json::Serializer::new(&mut buf),
+/// )
+/// .unwrap();
+/// assert_eq!(String::from_utf8(buf).unwrap(), "[1,3]");
+/// ```
+pub fn serialize_with<S, T, V, F, W>(iter: &T, f: F, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    F: Fn(V) -> W,
+    W: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    for value in iter {
+        seq.serialize_element(&f(value))?;
+    }
+    seq.end()
+}
+
+/// Serializes an iterator of `Result`s into a serde sequence,
+/// failing on the first `Err`.
+///
+/// The `Ok` values are serialized as elements.
+/// If an `Err` is encountered, serialization is aborted with a custom error
+/// carrying the `Display` representation of the error.
+/// The serializer may have already written the preceding elements,
+/// but the serialization result as a whole is an error.
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "serde_iter::seq::try_serialize")]
+///     bar: Vec<Result<i32, String>>,
+/// }
+///
+/// let foo = Foo {
+///     bar: vec![Ok(1), Err("bad input".to_owned())],
+/// };
+/// let err = serde_json::to_value(&foo).unwrap_err();
+/// assert_eq!(err.to_string(), "bad input");
+/// ```
+pub fn try_serialize<S, T, V, E>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = Result<V, E>> + Clone,
+    V: Serialize,
+    E: Display,
+{
+    let iter = iter.clone().into_iter();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    for value in iter {
+        let value = value.map_err(S::Error::custom)?;
+        seq.serialize_element(&value)?;
+    }
+    seq.end()
+}
+
+/// Serializes an iterator into a serde sequence with a configurable length hint.
+///
+/// Use [`SeqHint::Exact`](options/enum.SeqHint.html#variant.Exact) for length-prefixed formats,
+/// or [`SeqHint::None`](options/enum.SeqHint.html#variant.None) for formats that
+/// behave differently when the length is known in advance.
+/// `SeqHint::Exact` returns an error if the iterator does not report an exact size hint;
+/// use [`serialize_exact`] to enforce `ExactSizeIterator` at compile time instead.
+///
+/// # Example
+/// ```
+/// use serde_iter::seq::options::SeqHint;
+///
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "exact")]
+///     bar: Vec<u8>,
+/// }
+///
+/// fn exact<S: serde::Serializer>(bar: &Vec<u8>, serializer: S) -> Result<S::Ok, S::Error> {
+///     serde_iter::seq::serialize_hint(bar, SeqHint::Exact, serializer)
+/// }
+///
+/// let foo = Foo { bar: vec![1, 2] };
+/// assert_eq!(bincode::serialize(&foo).unwrap(), [2, 0, 0, 0, 0, 0, 0, 0, 1, 2]);
+/// ```
+pub fn serialize_hint<S, T, V>(iter: &T, hint: SeqHint, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let len = match hint {
+        SeqHint::None => None,
+        SeqHint::Lower => Some(iter.size_hint().0),
+        SeqHint::Exact => match iter.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(lower),
+            _ => return Err(S::Error::custom("iterator does not have an exact size")),
+        },
+    };
+    let mut seq = serializer.serialize_seq(len)?;
+    for value in iter {
+        seq.serialize_element(&value)?;
+    }
+    seq.end()
+}
+
+/// Serializes at most the first `n` elements of an iterator into a serde sequence.
+///
+/// The remaining elements are never pulled from the iterator,
+/// so this can be used to preview infinite or very large iterators, e.g. in logs.
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "preview")]
+///     bar: std::ops::RangeFrom<u32>,
+/// }
+///
+/// fn preview<S: serde::Serializer>(
+///     bar: &std::ops::RangeFrom<u32>,
+///     serializer: S,
+/// ) -> Result<S::Ok, S::Error> {
+///     serde_iter::seq::serialize_take(bar, 3, serializer)
+/// }
+///
+/// let foo = Foo { bar: 1.. };
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": [1, 2, 3]
+/// }));
+/// ```
+pub fn serialize_take<S, T, V>(iter: &T, n: usize, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    let iter = iter.clone().into_iter().take(n);
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    for value in iter {
+        seq.serialize_element(&value)?;
+    }
+    seq.end()
+}
+
+/// Serializes the elements of a double-ended iterator into a serde sequence in reverse order.
+///
+/// The iterator is reversed with `.rev()` instead of being collected,
+/// and the length hint is the same as for the original iterator.
+///
+/// # Example
+/// ```
+/// use std::collections::VecDeque;
+///
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "serde_iter::seq::serialize_rev")]
+///     bar: VecDeque<i32>,
+/// }
+///
+/// let foo = Foo {
+///     bar: vec![1, 2, 3].into(),
+/// };
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": [3, 2, 1]
+/// }));
+/// ```
+pub fn serialize_rev<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    T::IntoIter: DoubleEndedIterator,
+    V: Serialize,
+{
+    let iter = iter.clone().into_iter().rev();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    for value in iter {
+        seq.serialize_element(&value)?;
+    }
+    seq.end()
+}
+
+/// Serializes an iterator into a serde sequence, or `None` if the iterator is empty.
+///
+/// This is useful for APIs that distinguish "no data" (`null`) from an empty list (`[]`).
+/// The cloned iterator is peeked to check for emptiness,
+/// so no element is consumed twice.
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "serde_iter::seq::serialize_null_if_empty")]
+///     bar: Vec<i32>,
+/// }
+///
+/// assert_eq!(
+///     serde_json::to_value(&Foo { bar: vec![] }).unwrap(),
+///     serde_json::json!({"bar": null}),
+/// );
+/// assert_eq!(
+///     serde_json::to_value(&Foo { bar: vec![1, 2] }).unwrap(),
+///     serde_json::json!({"bar": [1, 2]}),
+/// );
+/// ```
+pub fn serialize_null_if_empty<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    let mut iter = iter.clone().into_iter().peekable();
+    if iter.peek().is_none() {
+        return serializer.serialize_none();
+    }
+
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    for value in iter {
+        seq.serialize_element(&value)?;
+    }
+    seq.end()
+}
+
+/// The performance-safe variant of [`serialize`], which only accepts `Copy` elements.
+///
+/// As explained in the [module-level documentation](index.html#cloning),
+/// iterators over owned values such as `String` may clone every element on each serialization.
+/// The `V: Copy` bound statically rejects such element types,
+/// so that performance-sensitive code is nudged towards iterating over references instead.
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo<'a> {
+///     #[serde(serialize_with = "serde_iter::seq::serialize_cheap")]
+///     bar: std::slice::Iter<'a, String>,
+/// }
+///
+/// let names = vec!["a".to_owned(), "b".to_owned()];
+/// let foo = Foo { bar: names.iter() };
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": ["a", "b"]
+/// }));
+/// ```
+///
+/// Iterating over owned `String`s fails to compile:
+/// ```compile_fail
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "serde_iter::seq::serialize_cheap")]
+///     bar: std::vec::IntoIter<String>,
+/// }
+///
+/// let names = vec!["a".to_owned(), "b".to_owned()];
+/// serde_json::to_value(&Foo { bar: names.into_iter() }).unwrap();
+/// ```
+pub fn serialize_cheap<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize + Copy,
+{
+    serialize(iter, serializer)
+}
+
+/// Serializes an iterator into a serde sequence wrapped in a newtype struct called `name`.
+///
+/// Formats that ignore newtype structs, such as JSON, produce the bare sequence,
+/// while formats that record them receive the correct framing,
+/// as if the sequence were the field of a `struct Name(Vec<V>)`.
+///
+/// # Example
+/// ```
+/// struct Ids(Vec<u32>);
+///
+/// impl serde::Serialize for Ids {
+///     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+///         serde_iter::seq::serialize_newtype("Ids", &self.0, serializer)
+///     }
+/// }
+///
+/// assert_eq!(
+///     serde_json::to_value(&Ids(vec![1, 2])).unwrap(),
+///     serde_json::json!([1, 2]),
+/// );
+/// ```
+pub fn serialize_newtype<S, T, V>(
+    name: &'static str,
+    iter: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    serializer.serialize_newtype_struct(name, &Newtype(iter))
+}
+
+struct Newtype<'a, T>(&'a T);
+
+impl<'a, T, V> Serialize for Newtype<'a, T>
+where
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(self.0, serializer)
+    }
+}
+
+/// Serializes an iterator of iterators into a single flat serde sequence.
+///
+/// The inner iterators are flattened one level,
+/// so `[[1, 2], [3]]` is serialized as `[1, 2, 3]`.
+/// Since the total number of elements is unknown without consuming the inner iterators,
+/// the sequence length is passed to the serializer as `None`.
+/// Use the [`nested`](../nested/index.html) module to keep the inner sequences instead.
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "serde_iter::seq::serialize_flatten")]
+///     bar: Vec<Vec<i32>>,
+/// }
+///
+/// let foo = Foo {
+///     bar: vec![vec![1, 2], vec![], vec![3]],
+/// };
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": [1, 2, 3]
+/// }));
+/// ```
+pub fn serialize_flatten<S, T, Inner, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = Inner> + Clone,
+    Inner: IntoIterator<Item = V>,
+    V: Serialize,
+{
+    let mut seq = serializer.serialize_seq(None)?;
+    for value in iter.clone().into_iter().flatten() {
+        seq.serialize_element(&value)?;
+    }
+    seq.end()
+}
+
+/// Serializes an iterator into a serde sequence of chunks with up to `N` elements each.
+///
+/// The elements are grouped in order, and the last chunk may contain fewer than `N` elements,
+/// so `[1, 2, 3, 4, 5, 6, 7]` is serialized as `[[1, 2, 3], [4, 5, 6], [7]]` if `N` is 3.
+/// The chunks are streamed from the iterator without collecting the elements.
+/// Serialization fails if `N` is zero.
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "serde_iter::seq::serialize_chunked::<_, _, _, 2>")]
+///     bar: std::ops::Range<u32>,
+/// }
+///
+/// let foo = Foo { bar: 1..6 };
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": [[1, 2], [3, 4], [5]]
+/// }));
+/// ```
+pub fn serialize_chunked<S, T, V, const N: usize>(
+    iter: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    if N == 0 {
+        return Err(S::Error::custom("chunk size must be nonzero"));
+    }
+
+    let iter = RefCell::new(iter.clone().into_iter().peekable());
+    let len = iter.borrow().size_hint().0.div_ceil(N);
+    let mut seq = serializer.serialize_seq(Some(len))?;
+    while iter.borrow_mut().peek().is_some() {
+        seq.serialize_element(&Chunk::<_, N>(&iter))?;
+    }
+    seq.end()
+}
+
+struct Chunk<'a, I: Iterator, const N: usize>(&'a RefCell<Peekable<I>>);
+
+impl<'a, I, const N: usize> Serialize for Chunk<'a, I, N>
+where
+    I: Iterator,
+    I::Item: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut iter = self
+            .0
+            .try_borrow_mut()
+            .map_err(|_| S::Error::custom("chunk is already being serialized"))?;
+        let len = match iter.size_hint() {
+            (lower, _) if lower >= N => Some(N),
+            (lower, Some(upper)) if lower == upper => Some(lower),
+            _ => None,
+        };
+        let mut seq = serializer.serialize_seq(len)?;
+        for value in iter.by_ref().take(N) {
+            seq.serialize_element(&value)?;
+        }
+        seq.end()
+    }
+}
+
+/// A source of fresh iterators, used instead of `Clone` by [`serialize_strategy`].
+///
+/// Some iterators are expensive to clone but cheap to recreate from a stored seed,
+/// such as the parameters of a range or a query.
+///
+/// *This trait requires the "alloc" feature to be enabled (enabled by default).*
+///
+/// # Example
+/// ```
+/// struct Evens {
+///     below: u32,
+/// }
+///
+/// impl serde_iter::seq::CloneStrategy<u32> for Evens {
+///     fn fresh(&self) -> Box<dyn Iterator<Item = u32> + '_> {
+///         Box::new((0..self.below).step_by(2))
+///     }
+/// }
+///
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "serde_iter::seq::serialize_strategy")]
+///     bar: Evens,
+/// }
+///
+/// let foo = Foo { bar: Evens { below: 7 } };
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": [0, 2, 4, 6]
+/// }));
+/// ```
+#[cfg(feature = "alloc")]
+pub trait CloneStrategy<V> {
+    /// Returns a new iterator over all elements, starting from the beginning.
+    fn fresh(&self) -> Box<dyn Iterator<Item = V> + '_>;
+}
+
+/// Serializes the elements of a fresh iterator from a [`CloneStrategy`] into a serde sequence.
+///
+/// Each serialization calls [`CloneStrategy::fresh`] instead of cloning an iterator.
+///
+/// *This function requires the "alloc" feature to be enabled (enabled by default).*
+#[cfg(feature = "alloc")]
+pub fn serialize_strategy<S, C, V>(strategy: &C, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    C: CloneStrategy<V> + ?Sized,
+    V: Serialize,
+{
+    let iter = strategy.fresh();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    for value in iter {
+        seq.serialize_element(&value)?;
+    }
+    seq.end()
+}
+
+/// Serializes an iterator of type-erased serializables into a serde sequence.
+///
+/// The elements can be any type that borrows a `dyn erased_serde::Serialize`,
+/// such as `Box<dyn erased_serde::Serialize>` or a reference to it,
+/// so elements of different types can be mixed in the same sequence,
+/// e.g. in plugin systems.
+/// Since boxes of trait objects do not implement `Clone`,
+/// this is typically called with an iterator over references to the boxes.
+///
+/// *This function requires the "erased" feature to be enabled.*
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "erased")]
+///     bar: Vec<Box<dyn erased_serde::Serialize>>,
+/// }
+///
+/// fn erased<S: serde::Serializer>(
+///     bar: &[Box<dyn erased_serde::Serialize>],
+///     serializer: S,
+/// ) -> Result<S::Ok, S::Error> {
+///     serde_iter::seq::serialize_erased(&bar.iter(), serializer)
+/// }
+///
+/// let foo = Foo {
+///     bar: vec![Box::new(1), Box::new("two"), Box::new([3.0])],
+/// };
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": [1, "two", [3.0]]
+/// }));
+/// ```
+#[cfg(feature = "erased")]
+pub fn serialize_erased<S, T, B>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = B> + Clone,
+    B: AsRef<dyn erased_serde::Serialize>,
+{
+    let iter = iter.clone().into_iter();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    for value in iter {
+        seq.serialize_element(value.as_ref())?;
+    }
+    seq.end()
+}
+
+/// Serializes an iterator into a serde sequence, clamping the length hint to `max_hint`.
+///
+/// Some formats preallocate buffers based on the length passed to `serialize_seq`,
+/// so an iterator that reports an excessive lower bound in its size hint,
+/// e.g. an adapter over untrusted input, could cause huge allocations.
+/// This function passes `Some(min(size_hint().0, max_hint))` instead.
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "clamped")]
+///     bar: Vec<u32>,
+/// }
+///
+/// fn clamped<S: serde::Serializer>(bar: &Vec<u32>, serializer: S) -> Result<S::Ok, S::Error> {
+///     serde_iter::seq::serialize_clamped(bar, 1024, serializer)
+/// }
+///
+/// let foo = Foo { bar: vec![1, 2, 3] };
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": [1, 2, 3]
+/// }));
+/// ```
+pub fn serialize_clamped<S, T, V>(
+    iter: &T,
+    max_hint: usize,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0.min(max_hint)))?;
+    for value in iter {
+        seq.serialize_element(&value)?;
+    }
+    seq.end()
+}
+
+/// Serializes an iterator into a serde sequence of unknown length.
+///
+/// The length is always passed to the serializer as `None`,
+/// even if the iterator reports a size hint.
+///
+/// Choose the variant according to the iterator and the format:
+/// - [`serialize`] passes the lower bound of the size hint,
+///   which is suitable for most iterators and self-describing formats.
+/// - [`serialize_exact`] passes the exact length,
+///   which is required by length-prefixed formats such as bincode.
+/// - `serialize_lazy` signals that the length is unknown,
+///   which is suitable for lazy iterators whose lower bound is misleading,
+///   e.g. for consumers that treat the length hint as a capacity.
+///   Formats that require a length reject it.
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "serde_iter::seq::serialize_lazy")]
+///     bar: std::iter::Take<std::ops::RangeFrom<u32>>,
+/// }
+///
+/// let foo = Foo { bar: (1..).take(3) };
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": [1, 2, 3]
+/// }));
+/// ```
+pub fn serialize_lazy<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    let mut seq = serializer.serialize_seq(None)?;
+    for value in iter.clone() {
+        seq.serialize_element(&value)?;
+    }
+    seq.end()
+}
+
+/// Serializes an iterator into a serde sequence of the `Some` values returned by `f`.
+///
+/// Each item is passed to `f`, and the item is skipped if `f` returns `None`.
+/// Since the number of skipped items is not known in advance,
+/// the sequence length is passed to the serializer as `None`.
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "parsed")]
+///     bar: Vec<&'static str>,
+/// }
+///
+/// fn parsed<S: serde::Serializer>(bar: &Vec<&'static str>, serializer: S) -> Result<S::Ok, S::Error> {
+///     serde_iter::seq::serialize_filter_map(bar, |s: &str| s.parse::<u32>().ok(), serializer)
+/// }
+///
+/// let foo = Foo {
+///     bar: vec!["1", "x", "3"],
+/// };
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": [1, 3]
+/// }));
+/// ```
+pub fn serialize_filter_map<S, T, V, F, W>(iter: &T, f: F, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    F: Fn(V) -> Option<W> + Clone,
+    W: Serialize,
+{
+    let mut seq = serializer.serialize_seq(None)?;
+    for value in iter.clone().into_iter().filter_map(f) {
+        seq.serialize_element(&value)?;
+    }
+    seq.end()
+}
+
+/// Serializes an iterator into a serde sequence, skipping consecutive duplicate elements.
+///
+/// An element is skipped if it is equal to the previously emitted element.
+/// Only adjacent duplicates are removed, similar to `Vec::dedup`;
+/// an element equal to an earlier but not immediately preceding one is still emitted.
+/// Since the number of skipped elements is not known in advance,
+/// the sequence length is passed to the serializer as `None`.
+///
+/// # Example
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(serialize_with = "serde_iter::seq::serialize_dedup")]
+///     bar: Vec<u32>,
+/// }
+///
+/// let foo = Foo {
+///     bar: vec![1, 1, 2, 2, 2, 1],
+/// };
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": [1, 2, 1]
+/// }));
+/// ```
+pub fn serialize_dedup<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize + PartialEq + Clone,
+{
+    let mut seq = serializer.serialize_seq(None)?;
+    let mut last: Option<V> = None;
+    for value in iter.clone() {
+        if last.as_ref() == Some(&value) {
+            continue;
+        }
+        seq.serialize_element(&value)?;
+        last = Some(value);
+    }
+    seq.end()
+}
+
+/// Serializes an iterator stored in a `RefCell` into a serde sequence,
+/// consuming it without cloning.
+///
+/// The iterator is taken out of the `RefCell` on the first call and drained.
+/// Unlike [`CloneOnce`](../struct.CloneOnce.html), which panics when serialized twice,
+/// subsequent calls return a custom serialization error.
+///
+/// *This function requires the "unstable-single-pass" feature to be enabled.*
+///
+/// # Example
+/// ```
+/// use std::cell::RefCell;
+///
+/// #[derive(serde::Serialize)]
+/// struct Foo<I: Iterator<Item = u32>> {
+///     #[serde(serialize_with = "serde_iter::seq::serialize_consuming")]
+///     bar: RefCell<Option<I>>,
+/// }
+///
+/// let mut v = vec![1, 2, 3];
+/// let foo = Foo {
+///     bar: RefCell::new(Some(v.drain(..))),
+/// };
+/// assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+///     "bar": [1, 2, 3]
+/// }));
+/// assert!(serde_json::to_value(&foo).is_err());
+/// ```
+#[cfg(feature = "unstable-single-pass")]
+pub fn serialize_consuming<S, I, V>(
+    iter: &RefCell<Option<I>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    I: IntoIterator<Item = V>,
+    V: Serialize,
+{
+    let iter = iter
+        .try_borrow_mut()
+        .map_err(|_| S::Error::custom("iterator is already being serialized"))?
+        .take()
+        .ok_or_else(|| S::Error::custom("iterator has already been consumed"))?
+        .into_iter();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    for value in iter {
+        seq.serialize_element(&value)?;
+    }
+    seq.end()
+}
+
 #[cfg(test)]
 mod tests {
+    use std::cell::{Cell, RefCell};
     use std::iter;
 
-    use serde::Serialize;
-    use serde_json::{json, to_value};
+    use serde::{Deserialize, Serialize};
+    use serde_json::value::Serializer as ValueSerializer;
+    use serde_json::{from_value, json, to_string, to_value};
+
+    #[cfg(feature = "alloc")]
+    use super::CloneStrategy;
+    use super::{AsSeq, SeqHint, WeightedTake};
 
     #[derive(Serialize)]
     struct Foo<T>
@@ -200,4 +1216,768 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn test_weighted_take() {
+        let take = WeightedTake::new([3_u64, 4, 2, 1], 9, |value: &u64| *value);
+        let value = to_value(take);
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!([3, 4, 2]));
+    }
+
+    #[test]
+    fn test_weighted_take_stops_at_budget() {
+        let take = WeightedTake::new([3_u64, 10, 1], 9, |value: &u64| *value);
+        let value = to_value(take);
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!([3]));
+    }
+
+    #[test]
+    fn test_serialize_ref_once_then_empty() {
+        #[derive(Serialize)]
+        struct Bar {
+            #[serde(serialize_with = "super::serialize_ref")]
+            bar: Cell<Option<std::vec::IntoIter<i32>>>,
+        }
+
+        let bar = Bar {
+            bar: Cell::new(Some(vec![1, 2].into_iter())),
+        };
+        let first = to_value(&bar).expect("Failed to serialize");
+        assert_eq!(first, json!({"bar": [1, 2]}));
+        assert!(bar.bar.take().is_none());
+
+        let second = to_value(&bar).expect("Failed to serialize");
+        assert_eq!(second, json!({"bar": []}));
+    }
+
+    #[test]
+    fn test_serialize_ref_bincode() {
+        #[derive(Serialize)]
+        struct Bar {
+            #[serde(serialize_with = "super::serialize_ref")]
+            bar: Cell<Option<std::vec::IntoIter<u8>>>,
+        }
+
+        let single_pass = Bar {
+            bar: Cell::new(Some(vec![1, 2, 3].into_iter())),
+        };
+        let mut written = Vec::new();
+        let result = bincode::serialize_into(&mut written, &single_pass);
+        result.expect("Failed to serialize");
+        let decoded = bincode::deserialize::<Vec<u8>>(&written).ok();
+        assert_eq!(decoded, Some(vec![1, 2, 3]));
+
+        // The size-counting pass of `bincode::serialize` consumes the iterator.
+        let two_pass = Bar {
+            bar: Cell::new(Some(vec![1, 2, 3].into_iter())),
+        };
+        let serialized = bincode::serialize(&two_pass).expect("Failed to serialize");
+        let decoded_empty = bincode::deserialize::<Vec<u8>>(&serialized).ok();
+        assert_eq!(decoded_empty, Some(vec![]));
+    }
+
+    #[test]
+    fn test_serialize_exact_length_prefix() {
+        #[derive(Serialize)]
+        struct Inexact<T: Iterator<Item = u8> + Clone> {
+            #[serde(with = "super")]
+            bar: T,
+        }
+
+        #[derive(Serialize)]
+        struct Exact<T: ExactSizeIterator<Item = u8> + Clone> {
+            #[serde(serialize_with = "super::serialize_exact")]
+            bar: T,
+        }
+
+        let values = [1_u8, 2, 3, 4];
+
+        // `Filter` has a size hint of `(0, Some(4))`, so the length prefix is 0.
+        let filtered = values.iter().copied().filter(|value| value % 2 == 0);
+        let inexact = bincode::serialize(&Inexact { bar: filtered });
+        let inexact = inexact.expect("Failed to serialize");
+        assert_eq!(inexact, [0, 0, 0, 0, 0, 0, 0, 0, 2, 4]);
+        assert_ne!(
+            bincode::deserialize::<Vec<u8>>(&inexact).ok(),
+            Some(vec![2, 4])
+        );
+
+        let exact = bincode::serialize(&Exact {
+            bar: values.iter().copied().skip(2),
+        });
+        let exact = exact.expect("Failed to serialize");
+        assert_eq!(exact, [2, 0, 0, 0, 0, 0, 0, 0, 3, 4]);
+        assert_eq!(
+            bincode::deserialize::<Vec<u8>>(&exact).ok(),
+            Some(vec![3, 4])
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_round_trip() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Bar {
+            #[serde(with = "super")]
+            bar: Vec<i32>,
+        }
+
+        let value = to_value(Bar { bar: vec![1, 2, 3] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": [1, 2, 3]}));
+
+        let bar: Bar = from_value(value).expect("Failed to deserialize");
+        assert_eq!(bar, Bar { bar: vec![1, 2, 3] });
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_deserialize_untrusted_length() {
+        #[derive(Deserialize, Debug)]
+        struct Bar {
+            #[serde(with = "super")]
+            _bar: Vec<u64>,
+        }
+
+        // The length prefix claims far more elements than could ever be allocated.
+        let input = 0x0fff_ffff_ffff_ffff_u64.to_le_bytes();
+        assert!(bincode::deserialize::<Bar>(&input).is_err());
+    }
+
+    #[test]
+    fn test_serialize_with() {
+        fn lengths<S: serde::Serializer>(
+            iter: &[&'static str; 3],
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            super::serialize_with(iter, str::len, serializer)
+        }
+
+        #[derive(Serialize)]
+        struct Bar {
+            #[serde(serialize_with = "lengths")]
+            bar: [&'static str; 3],
+        }
+
+        let value = to_value(Bar {
+            bar: ["a", "", "abc"],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": [1, 0, 3]}));
+    }
+
+    #[test]
+    fn test_try_serialize() {
+        #[derive(Serialize)]
+        struct Bar {
+            #[serde(serialize_with = "super::try_serialize")]
+            bar: Vec<Result<i32, String>>,
+        }
+
+        let ok = to_value(Bar {
+            bar: vec![Ok(1), Ok(2)],
+        });
+        let ok = ok.expect("Failed to serialize");
+        assert_eq!(ok, json!({"bar": [1, 2]}));
+
+        let err = to_string(&Bar {
+            bar: vec![Ok(1), Ok(2), Err("invalid token".to_owned()), Ok(4)],
+        });
+        let err = err.expect_err("Expected an error");
+        assert!(err.to_string().contains("invalid token"));
+    }
+
+    #[cfg(feature = "unstable-single-pass")]
+    #[test]
+    fn test_serialize_consuming() {
+        #[derive(Serialize)]
+        struct Bar {
+            #[serde(serialize_with = "super::serialize_consuming")]
+            bar: RefCell<Option<std::vec::IntoIter<i32>>>,
+        }
+
+        let bar = Bar {
+            bar: RefCell::new(Some(vec![1, 2].into_iter())),
+        };
+        let first = to_value(&bar).expect("Failed to serialize");
+        assert_eq!(first, json!({"bar": [1, 2]}));
+
+        let second = to_value(&bar).expect_err("Expected an error");
+        assert_eq!(second.to_string(), "iterator has already been consumed");
+    }
+
+    struct Hinted<T>(T, SeqHint);
+
+    impl<T: IntoIterator<Item = u8> + Clone> Serialize for Hinted<T> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize_hint(&self.0, self.1, serializer)
+        }
+    }
+
+    #[test]
+    fn test_serialize_hint_length_prefix() {
+        let values = [1_u8, 2, 3, 4];
+        let filtered = values.iter().copied().filter(|value| value % 2 == 0);
+        let skipped = values.iter().copied().skip(2);
+
+        let none_bincode = bincode::serialize(&Hinted(skipped.clone(), SeqHint::None));
+        assert!(none_bincode.is_err());
+        let none_json = to_value(Hinted(skipped.clone(), SeqHint::None));
+        assert_eq!(none_json.expect("Failed to serialize"), json!([3, 4]));
+
+        let lower = bincode::serialize(&Hinted(filtered.clone(), SeqHint::Lower));
+        let lower = lower.expect("Failed to serialize");
+        assert_eq!(lower, [0, 0, 0, 0, 0, 0, 0, 0, 2, 4]);
+
+        let exact = bincode::serialize(&Hinted(skipped, SeqHint::Exact));
+        let exact = exact.expect("Failed to serialize");
+        assert_eq!(exact, [2, 0, 0, 0, 0, 0, 0, 0, 3, 4]);
+        assert_eq!(
+            bincode::deserialize::<Vec<u8>>(&exact).ok(),
+            Some(vec![3, 4])
+        );
+
+        let inexact = bincode::serialize(&Hinted(filtered, SeqHint::Exact));
+        assert!(inexact.is_err());
+    }
+
+    struct Taken<T>(T, usize);
+
+    impl<T: IntoIterator<Item = u8> + Clone> Serialize for Taken<T> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize_take(&self.0, self.1, serializer)
+        }
+    }
+
+    #[test]
+    fn test_serialize_take_infinite() {
+        let value = to_value(Taken(iter::repeat(7), 5));
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!([7, 7, 7, 7, 7]));
+
+        let prefixed = bincode::serialize(&Taken(iter::repeat(7), 2));
+        let prefixed = prefixed.expect("Failed to serialize");
+        assert_eq!(prefixed, [2, 0, 0, 0, 0, 0, 0, 0, 7, 7]);
+    }
+
+    #[test]
+    fn test_serialize_take_short() {
+        let value = to_value(Taken(vec![1, 2], 5));
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!([1, 2]));
+    }
+
+    #[test]
+    fn test_serialize_take_zero() {
+        let untouched = iter::from_fn(|| -> Option<u8> { panic!("Iterator was consumed") });
+        let value = to_value(Taken(untouched, 0));
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!([]));
+    }
+
+    #[test]
+    fn test_serialize_rev() {
+        #[derive(Serialize)]
+        struct Rev<T: DoubleEndedIterator<Item = u8> + Clone> {
+            #[serde(serialize_with = "super::serialize_rev")]
+            bar: T,
+        }
+
+        let values = [1_u8, 2, 3];
+        let value = to_value(Rev {
+            bar: values.iter().copied(),
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": [3, 2, 1]}));
+
+        let prefixed = bincode::serialize(&Rev {
+            bar: values.iter().copied(),
+        });
+        let prefixed = prefixed.expect("Failed to serialize");
+        assert_eq!(prefixed, [3, 0, 0, 0, 0, 0, 0, 0, 3, 2, 1]);
+    }
+
+    #[derive(Serialize)]
+    struct NullIfEmpty<T: IntoIterator<Item = i32> + Clone> {
+        #[serde(serialize_with = "super::serialize_null_if_empty")]
+        bar: T,
+    }
+
+    #[test]
+    fn test_serialize_null_if_empty() {
+        let value = to_value(NullIfEmpty { bar: iter::empty() });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": null}));
+    }
+
+    #[test]
+    fn test_serialize_null_if_not_empty() {
+        let calls = Cell::new(0);
+        let value = to_value(NullIfEmpty {
+            bar: [1, 2, 3].iter().map(|&value| {
+                calls.set(calls.get() + 1);
+                value
+            }),
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": [1, 2, 3]}));
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn test_serialize_cheap() {
+        #[derive(Serialize)]
+        struct Cheap<'a> {
+            #[serde(serialize_with = "super::serialize_cheap")]
+            bar: std::slice::Iter<'a, String>,
+        }
+
+        let names = ["a".to_owned(), "b".to_owned()];
+        let value = to_value(Cheap { bar: names.iter() });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": ["a", "b"]}));
+    }
+
+    /// The calls recorded by a `Recorder`.
+    #[derive(Default)]
+    struct Recorded {
+        newtype_names: RefCell<Vec<&'static str>>,
+        seq_lens: RefCell<Vec<Option<usize>>>,
+    }
+
+    /// A `serde_json::Value` serializer that records the names of newtype structs
+    /// and the lengths passed to `serialize_seq`.
+    struct Recorder<'a>(&'a Recorded);
+
+    impl<'a> serde::Serializer for Recorder<'a> {
+        type Ok = serde_json::Value;
+        type Error = serde_json::Error;
+        type SerializeSeq = <ValueSerializer as serde::Serializer>::SerializeSeq;
+        type SerializeTuple = <ValueSerializer as serde::Serializer>::SerializeTuple;
+        type SerializeTupleStruct = <ValueSerializer as serde::Serializer>::SerializeTupleStruct;
+        type SerializeTupleVariant = <ValueSerializer as serde::Serializer>::SerializeTupleVariant;
+        type SerializeMap = <ValueSerializer as serde::Serializer>::SerializeMap;
+        type SerializeStruct = <ValueSerializer as serde::Serializer>::SerializeStruct;
+        type SerializeStructVariant =
+            <ValueSerializer as serde::Serializer>::SerializeStructVariant;
+
+        fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_bool(v)
+        }
+        fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_i8(v)
+        }
+        fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_i16(v)
+        }
+        fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_i32(v)
+        }
+        fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_i64(v)
+        }
+        fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_u8(v)
+        }
+        fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_u16(v)
+        }
+        fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_u32(v)
+        }
+        fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_u64(v)
+        }
+        fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_f32(v)
+        }
+        fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_f64(v)
+        }
+        fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_char(v)
+        }
+        fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_str(v)
+        }
+        fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_bytes(v)
+        }
+        fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_none()
+        }
+        fn serialize_some<T: Serialize + ?Sized>(self, v: &T) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_some(v)
+        }
+        fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_unit()
+        }
+        fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_unit_struct(name)
+        }
+        fn serialize_unit_variant(
+            self,
+            name: &'static str,
+            index: u32,
+            variant: &'static str,
+        ) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_unit_variant(name, index, variant)
+        }
+        fn serialize_newtype_struct<T: Serialize + ?Sized>(
+            self,
+            name: &'static str,
+            value: &T,
+        ) -> Result<Self::Ok, Self::Error> {
+            self.0.newtype_names.borrow_mut().push(name);
+            ValueSerializer.serialize_newtype_struct(name, value)
+        }
+        fn serialize_newtype_variant<T: Serialize + ?Sized>(
+            self,
+            name: &'static str,
+            index: u32,
+            variant: &'static str,
+            value: &T,
+        ) -> Result<Self::Ok, Self::Error> {
+            ValueSerializer.serialize_newtype_variant(name, index, variant, value)
+        }
+        fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+            self.0.seq_lens.borrow_mut().push(len);
+            ValueSerializer.serialize_seq(len)
+        }
+        fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+            ValueSerializer.serialize_tuple(len)
+        }
+        fn serialize_tuple_struct(
+            self,
+            name: &'static str,
+            len: usize,
+        ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+            ValueSerializer.serialize_tuple_struct(name, len)
+        }
+        fn serialize_tuple_variant(
+            self,
+            name: &'static str,
+            index: u32,
+            variant: &'static str,
+            len: usize,
+        ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+            ValueSerializer.serialize_tuple_variant(name, index, variant, len)
+        }
+        fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+            ValueSerializer.serialize_map(len)
+        }
+        fn serialize_struct(
+            self,
+            name: &'static str,
+            len: usize,
+        ) -> Result<Self::SerializeStruct, Self::Error> {
+            ValueSerializer.serialize_struct(name, len)
+        }
+        fn serialize_struct_variant(
+            self,
+            name: &'static str,
+            index: u32,
+            variant: &'static str,
+            len: usize,
+        ) -> Result<Self::SerializeStructVariant, Self::Error> {
+            ValueSerializer.serialize_struct_variant(name, index, variant, len)
+        }
+    }
+
+    struct Ids(Vec<u32>);
+
+    impl Serialize for Ids {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize_newtype("Ids", &self.0, serializer)
+        }
+    }
+
+    #[test]
+    fn test_serialize_newtype() {
+        let value = to_value(Ids(vec![1, 2, 3]));
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!([1, 2, 3]));
+
+        let recorded = Recorded::default();
+        let output = Ids(vec![1, 2, 3]).serialize(Recorder(&recorded));
+        let output = output.expect("Failed to serialize");
+        assert_eq!(output, json!([1, 2, 3]));
+        assert_eq!(recorded.newtype_names.into_inner(), vec!["Ids"]);
+    }
+
+    #[test]
+    fn test_serialize_flatten() {
+        #[derive(Serialize)]
+        struct Flatten<T: IntoIterator<Item = Vec<i32>> + Clone> {
+            #[serde(serialize_with = "super::serialize_flatten")]
+            bar: T,
+        }
+
+        let value = to_value(Flatten {
+            bar: vec![vec![1, 2], vec![3]],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": [1, 2, 3]}));
+
+        let empty = to_value(Flatten {
+            bar: vec![vec![], vec![]],
+        });
+        let empty = empty.expect("Failed to serialize");
+        assert_eq!(empty, json!({"bar": []}));
+    }
+
+    #[test]
+    fn test_serialize_json_ignores_hint() {
+        #[derive(Serialize)]
+        struct Plain<T: IntoIterator<Item = u8> + Clone> {
+            #[serde(with = "super")]
+            bar: T,
+        }
+
+        let values = [1_u8, 2, 3, 4];
+        let doubled = values.iter().map(|value| value * 2).skip(2);
+        let plain = to_string(&Plain {
+            bar: doubled.clone(),
+        });
+        let plain = plain.expect("Failed to serialize");
+        assert_eq!(plain, r#"{"bar":[6,8]}"#);
+
+        for hint in [SeqHint::None, SeqHint::Lower] {
+            let hinted = to_string(&Hinted(doubled.clone(), hint));
+            let hinted = hinted.expect("Failed to serialize");
+            assert_eq!(format!(r#"{{"bar":{}}}"#, hinted), plain);
+        }
+    }
+
+    #[derive(Serialize)]
+    struct Chunked<T: IntoIterator<Item = u32> + Clone> {
+        #[serde(serialize_with = "super::serialize_chunked::<_, _, _, 3>")]
+        bar: T,
+    }
+
+    #[test]
+    fn test_serialize_chunked_exact() {
+        let value = to_value(Chunked { bar: 1..7 });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": [[1, 2, 3], [4, 5, 6]]}));
+
+        let empty = to_value(Chunked { bar: 0..0 });
+        let empty = empty.expect("Failed to serialize");
+        assert_eq!(empty, json!({"bar": []}));
+    }
+
+    #[test]
+    fn test_serialize_chunked_partial() {
+        let value = to_value(Chunked { bar: 1..8 });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": [[1, 2, 3], [4, 5, 6], [7]]}));
+
+        let prefixed = bincode::serialize(&Chunked { bar: 1_u32..5 });
+        let prefixed = prefixed.expect("Failed to serialize");
+        let decoded = bincode::deserialize::<Vec<Vec<u32>>>(&prefixed).ok();
+        assert_eq!(decoded, Some(vec![vec![1, 2, 3], vec![4]]));
+    }
+
+    #[test]
+    fn test_serialize_chunked_zero() {
+        #[derive(Serialize)]
+        struct Zero {
+            #[serde(serialize_with = "super::serialize_chunked::<_, _, _, 0>")]
+            bar: Vec<u32>,
+        }
+
+        let err = to_value(Zero { bar: vec![1] });
+        let err = err.expect_err("Serialized with zero chunk size");
+        assert_eq!(err.to_string(), "chunk size must be nonzero");
+    }
+
+    #[cfg(feature = "alloc")]
+    struct Squares {
+        count: u32,
+        fresh_calls: Cell<u32>,
+    }
+
+    #[cfg(feature = "alloc")]
+    impl CloneStrategy<u32> for Squares {
+        fn fresh(&self) -> Box<dyn Iterator<Item = u32> + '_> {
+            self.fresh_calls.set(self.fresh_calls.get() + 1);
+            Box::new((1..=self.count).map(|x| x * x))
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_serialize_strategy() {
+        #[derive(Serialize)]
+        struct Strategy {
+            #[serde(serialize_with = "super::serialize_strategy")]
+            bar: Squares,
+        }
+
+        let strategy = Strategy {
+            bar: Squares {
+                count: 3,
+                fresh_calls: Cell::new(0),
+            },
+        };
+        let first = to_value(&strategy).expect("Failed to serialize");
+        let second = to_value(&strategy).expect("Failed to serialize");
+        assert_eq!(first, json!({"bar": [1, 4, 9]}));
+        assert_eq!(second, first);
+        assert_eq!(strategy.bar.fresh_calls.get(), 2);
+    }
+
+    #[cfg(feature = "erased")]
+    #[test]
+    fn test_serialize_erased() {
+        #[derive(Serialize)]
+        struct Erased<'a> {
+            #[serde(serialize_with = "super::serialize_erased")]
+            bar: std::slice::Iter<'a, Box<dyn erased_serde::Serialize>>,
+        }
+
+        let items: Vec<Box<dyn erased_serde::Serialize>> = vec![Box::new(1), Box::new("two")];
+        let value = to_value(Erased { bar: items.iter() });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": [1, "two"]}));
+    }
+
+    /// An iterator that claims to have far more elements than it yields.
+    #[derive(Clone)]
+    struct Liar(std::vec::IntoIter<u8>);
+
+    impl Iterator for Liar {
+        type Item = u8;
+
+        fn next(&mut self) -> Option<u8> {
+            self.0.next()
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (usize::MAX, None)
+        }
+    }
+
+    struct Clamped<T>(T, usize);
+
+    impl<T: IntoIterator<Item = u8> + Clone> Serialize for Clamped<T> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize_clamped(&self.0, self.1, serializer)
+        }
+    }
+
+    #[test]
+    fn test_serialize_clamped() {
+        // bincode writes the length hint as a u64 prefix.
+        let clamped = bincode::serialize(&Clamped(Liar(vec![1, 2].into_iter()), 2));
+        let clamped = clamped.expect("Failed to serialize");
+        assert_eq!(clamped, [2, 0, 0, 0, 0, 0, 0, 0, 1, 2]);
+
+        let unclamped = bincode::serialize(&Clamped(vec![1, 2, 3], 100));
+        let unclamped = unclamped.expect("Failed to serialize");
+        assert_eq!(unclamped, [3, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3]);
+
+        for max_hint in 0_u8..4 {
+            let empty = Liar(vec![].into_iter());
+            let hinted = bincode::serialize(&Clamped(empty, usize::from(max_hint)));
+            let hinted = hinted.expect("Failed to serialize");
+            let prefix = bincode::deserialize::<u64>(&hinted).ok();
+            assert!(prefix.is_some_and(|prefix| prefix <= u64::from(max_hint)));
+        }
+    }
+
+    #[test]
+    fn test_serialize_lazy() {
+        struct Lazy(Vec<u32>);
+
+        impl Serialize for Lazy {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                super::serialize_lazy(&self.0, serializer)
+            }
+        }
+
+        let recorded = Recorded::default();
+        let output = Lazy(vec![1, 2, 3]).serialize(Recorder(&recorded));
+        let output = output.expect("Failed to serialize");
+        assert_eq!(output, json!([1, 2, 3]));
+        assert_eq!(recorded.seq_lens.into_inner(), vec![None]);
+    }
+
+    #[test]
+    fn test_serialize_filter_map() {
+        #[derive(Serialize)]
+        struct Evens {
+            #[serde(serialize_with = "evens")]
+            bar: Vec<u32>,
+        }
+
+        fn evens<S: serde::Serializer>(bar: &Vec<u32>, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize_filter_map(
+                bar,
+                |value: u32| value.is_multiple_of(2).then(|| value.to_string()),
+                serializer,
+            )
+        }
+
+        let value = to_value(Evens {
+            bar: vec![1, 2, 3, 4, 6],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!({"bar": ["2", "4", "6"]}));
+
+        let empty = to_value(Evens { bar: vec![1, 3] });
+        let empty = empty.expect("Failed to serialize");
+        assert_eq!(empty, json!({"bar": []}));
+    }
+
+    #[test]
+    fn test_as_seq() {
+        let standalone = to_value(AsSeq((1..4).map(|x| x * 2)));
+        let standalone = standalone.expect("Failed to serialize");
+        assert_eq!(standalone, json!([2, 4, 6]));
+
+        #[derive(Serialize)]
+        struct Nested {
+            bar: AsSeq<iter::Chain<std::vec::IntoIter<u32>, std::option::IntoIter<u32>>>,
+            baz: Vec<AsSeq<std::ops::Range<u32>>>,
+        }
+
+        let nested = to_value(Nested {
+            bar: AsSeq(vec![1, 2].into_iter().chain(Some(3))),
+            baz: vec![AsSeq(0..2), AsSeq(5..6)],
+        });
+        let nested = nested.expect("Failed to serialize");
+        assert_eq!(nested, json!({"bar": [1, 2, 3], "baz": [[0, 1], [5]]}));
+    }
+
+    #[test]
+    fn test_serialize_dedup() {
+        #[derive(Serialize)]
+        struct Dedup<T>
+        where
+            T: IntoIterator + Clone,
+            T::Item: Serialize + PartialEq + Clone,
+        {
+            #[serde(serialize_with = "super::serialize_dedup")]
+            bar: T,
+        }
+
+        let numbers = to_value(Dedup {
+            bar: vec![1, 1, 2, 2, 1],
+        });
+        let numbers = numbers.expect("Failed to serialize");
+        assert_eq!(numbers, json!({"bar": [1, 2, 1]}));
+
+        let strings = to_value(Dedup {
+            bar: ["a", "a", "a"].iter(),
+        });
+        let strings = strings.expect("Failed to serialize");
+        assert_eq!(strings, json!({"bar": ["a"]}));
+
+        let empty = to_value(Dedup { bar: 0..0 });
+        let empty = empty.expect("Failed to serialize");
+        assert_eq!(empty, json!({"bar": []}));
+    }
 }
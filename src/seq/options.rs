@@ -0,0 +1,21 @@
+//! Options for configuring the serializer functions in the [`seq`](../index.html) module.
+
+/// Determines the length passed to `Serializer::serialize_seq`.
+///
+/// Self-describing formats such as JSON ignore the length,
+/// but length-prefixed formats such as bincode reject `None`
+/// and write an incorrect prefix if the length is only a lower bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeqHint {
+    /// Passes `None`, i.e. the length is unknown.
+    None,
+    /// Passes the lower bound of the iterator's size hint.
+    ///
+    /// This is the behaviour of [`seq::serialize`](../fn.serialize.html).
+    Lower,
+    /// Passes the exact length of the iterator.
+    ///
+    /// The iterator must report an exact size hint, as every `ExactSizeIterator` does.
+    /// Serialization fails if the lower and upper bounds of the size hint differ.
+    Exact,
+}
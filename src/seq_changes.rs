@@ -0,0 +1,125 @@
+//! Serializes an iterator of serializables into a serde sequence,
+//! marking each element that differs from the previous one.
+//!
+//! Each element is wrapped as `{"changed": bool, "value": ...}`,
+//! where `changed` is `true` if the element is not equal to the previous element.
+//! The first element is always marked as changed.
+//! This is useful for diff-friendly logs.
+//!
+//! *This module requires the "seq_changes" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::seq_changes")]
+//!     bar: Vec<i32>,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: vec![1, 1, 2],
+//! };
+//! assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+//!     "bar": [
+//!         {"changed": true, "value": 1},
+//!         {"changed": false, "value": 1},
+//!         {"changed": true, "value": 2},
+//!     ]
+//! }));
+//! ```
+
+use serde::ser::{Serialize, SerializeSeq, SerializeStruct, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize + PartialEq,
+{
+    let iter = iter.clone().into_iter();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    let mut prev = None;
+    for value in iter {
+        let changed = prev.as_ref() != Some(&value);
+        seq.serialize_element(&Change {
+            changed,
+            value: &value,
+        })?;
+        prev = Some(value);
+    }
+    seq.end()
+}
+
+struct Change<'a, V> {
+    changed: bool,
+    value: &'a V,
+}
+
+impl<'a, V> Serialize for Change<'a, V>
+where
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut st = serializer.serialize_struct("Change", 2)?;
+        st.serialize_field("changed", &self.changed)?;
+        st.serialize_field("value", self.value)?;
+        st.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde_json::{json, to_value, Value};
+
+    #[derive(Serialize)]
+    struct Foo<T>
+    where
+        T: IntoIterator<Item = i32> + Clone,
+    {
+        #[serde(with = "super")]
+        bar: T,
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo { bar: vec![] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": []
+            })
+        );
+    }
+
+    #[test]
+    fn test_changes() {
+        let value = to_value(Foo {
+            bar: vec![1, 1, 2, 2, 3],
+        });
+        let value = value.expect("Failed to serialize");
+        let flags: Vec<&Value> = match value.get("bar") {
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(|item| item.get("changed"))
+                .collect(),
+            _ => panic!("Expected an array"),
+        };
+        assert_eq!(
+            flags,
+            vec![
+                &json!(true),
+                &json!(false),
+                &json!(true),
+                &json!(false),
+                &json!(true)
+            ]
+        );
+        assert_eq!(value.pointer("/bar/4/value"), Some(&json!(3)));
+    }
+}
@@ -0,0 +1,104 @@
+//! Serializes an iterator of `Display` types into a serde sequence of strings.
+//!
+//! Each element is serialized as its `to_string()` representation,
+//! without mapping the iterator beforehand.
+//! This is useful for types like `std::net::IpAddr` whose canonical form is a string.
+//!
+//! *This module requires the "seq_display" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+//!
+//! #[derive(serde::Serialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::seq_display")]
+//!     bar: Vec<IpAddr>,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: vec![IpAddr::V4(Ipv4Addr::LOCALHOST), IpAddr::V6(Ipv6Addr::LOCALHOST)],
+//! };
+//! assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+//!     "bar": ["127.0.0.1", "::1"]
+//! }));
+//! ```
+
+use core::fmt::Display;
+
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Display,
+{
+    let iter = iter.clone().into_iter();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    for value in iter {
+        seq.serialize_element(&AsStr(&value))?;
+    }
+    seq.end()
+}
+
+struct AsStr<'a, V>(&'a V);
+
+impl<'a, V> Serialize for AsStr<'a, V>
+where
+    V: Display,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    use serde::Serialize;
+    use serde_json::{json, to_value};
+
+    #[derive(Serialize)]
+    struct Foo<T>
+    where
+        T: IntoIterator<Item = IpAddr> + Clone,
+    {
+        #[serde(with = "super")]
+        bar: T,
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo { bar: vec![] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": []
+            })
+        );
+    }
+
+    #[test]
+    fn test_ip_addr() {
+        let value = to_value(Foo {
+            bar: vec![
+                IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)),
+                IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)),
+            ],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": ["192.168.0.1", "2001:db8::1"]
+            })
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Serializes an iterator of serializables into a serde sequence,
+//! failing on the first element that violates a predicate.
+//!
+//! Unlike filtering, invalid elements are rejected rather than dropped,
+//! which is useful for enforcing schema constraints at output time.
+//! Every element is validated in a first pass over a clone of the iterator
+//! before the sequence is started,
+//! so a streaming serializer never receives a partial sequence.
+//! As a result, the iterator is cloned and iterated twice, and the predicate is called once
+//! per element in the first pass.
+//!
+//! *This module requires the "seq_validated" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! let mut buf = Vec::new();
+//! serde_iter::seq_validated::serialize(
+//!     &vec![1, 2, 3],
+//!     |value: &i32| *value > 0,
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), "[1,2,3]");
+//! ```
+
+use serde::ser::{Error, Serialize, SerializeSeq, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+///
+/// Returns an error naming the index of the first element for which `pred` returns `false`,
+/// before anything is passed to the serializer.
+pub fn serialize<S, T, V, F>(iter: &T, pred: F, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+    F: Fn(&V) -> bool,
+{
+    if let Some(index) = iter.clone().into_iter().position(|value| !pred(&value)) {
+        return Err(S::Error::custom(format_args!(
+            "element at index {} failed validation",
+            index
+        )));
+    }
+
+    let iter = iter.clone().into_iter();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    for value in iter {
+        seq.serialize_element(&value)?;
+    }
+    seq.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_value};
+
+    type Values = [i32; 4];
+
+    fn non_negative<S: Serializer>(iter: &Values, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, |value: &i32| *value >= 0, serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(serialize_with = "non_negative")]
+        bar: Values,
+    }
+
+    #[test]
+    fn test_valid() {
+        let value = to_value(Foo { bar: [0, 1, 2, 3] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": [0, 1, 2, 3]
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let err = to_value(Foo { bar: [0, 1, -2, 3] });
+        let err = err.expect_err("Expected a validation error");
+        assert_eq!(err.to_string(), "element at index 2 failed validation");
+    }
+
+    #[test]
+    fn test_invalid_writes_nothing() {
+        let mut written = Vec::new();
+        let result = serde_json::to_writer(&mut written, &Foo { bar: [0, 1, -2, 3] });
+        assert!(result.is_err());
+        assert_eq!(written, br#"{"bar":"#);
+    }
+}
@@ -0,0 +1,137 @@
+//! Serializes an iterator of serializables into a serde sequence without duplicates.
+//!
+//! This is useful for iterators that logically represent sets,
+//! such as iterators computed on the fly that may yield the same element more than once.
+//!
+//! The `serialize` function requires `V: Hash + Eq`,
+//! and emits the first occurrence of each element in iteration order,
+//! remembering the emitted elements in a `HashSet`.
+//! Since the number of distinct elements is unknown in advance,
+//! the sequence length is not passed to the serializer.
+//!
+//! The [`serialize_ord`](fn.serialize_ord.html) function requires `V: Ord` instead,
+//! and collects the elements into a `BTreeSet`,
+//! so the distinct elements are emitted in sorted order.
+//!
+//! *This module requires the "set" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::set")]
+//!     bar: Vec<i32>,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: vec![3, 1, 3, 2, 1],
+//! };
+//! assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+//!     "bar": [3, 1, 2]
+//! }));
+//! ```
+
+use std::collections::{BTreeSet, HashSet};
+use std::hash::Hash;
+
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize + Hash + Eq,
+{
+    let mut seq = serializer.serialize_seq(None)?;
+    let mut seen = HashSet::new();
+    for value in iter.clone() {
+        if !seen.contains(&value) {
+            seq.serialize_element(&value)?;
+            seen.insert(value);
+        }
+    }
+    seq.end()
+}
+
+/// Serializes the distinct elements of the iterator in sorted order.
+///
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize_ord<S, T, V>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize + Ord,
+{
+    let values: BTreeSet<V> = iter.clone().into_iter().collect();
+
+    let mut seq = serializer.serialize_seq(Some(values.len()))?;
+    for value in &values {
+        seq.serialize_element(value)?;
+    }
+    seq.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde_json::{json, to_value};
+
+    #[derive(Serialize)]
+    struct Foo<T>
+    where
+        T: IntoIterator<Item = &'static str> + Clone,
+    {
+        #[serde(with = "super")]
+        bar: T,
+    }
+
+    #[derive(Serialize)]
+    struct Sorted<T>
+    where
+        T: IntoIterator<Item = &'static str> + Clone,
+    {
+        #[serde(serialize_with = "super::serialize_ord")]
+        bar: T,
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo { bar: vec![] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": []
+            })
+        );
+    }
+
+    #[test]
+    fn test_dedup() {
+        let value = to_value(Foo {
+            bar: vec!["b", "a", "b", "c", "a"],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": ["b", "a", "c"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_sorted() {
+        let value = to_value(Sorted {
+            bar: vec!["b", "a", "b", "c", "a"],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": ["a", "b", "c"]
+            })
+        );
+    }
+}
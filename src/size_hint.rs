@@ -0,0 +1,17 @@
+//! Caps length hints read from untrusted input before preallocating.
+
+use core::mem::size_of;
+
+/// The maximum number of bytes preallocated from a length hint.
+const MAX_PREALLOC_BYTES: usize = 1024 * 1024;
+
+/// Returns the capacity to preallocate for `hint` elements of type `T`.
+///
+/// The length hint of a deserializer comes from the input,
+/// so it is capped to at most 1 MiB worth of elements,
+/// similar to `serde`'s private `size_hint::cautious`.
+/// Collections still grow beyond this as elements are actually read.
+pub(crate) fn cautious<T>(hint: Option<usize>) -> usize {
+    let max = MAX_PREALLOC_BYTES / size_of::<T>().max(1);
+    hint.unwrap_or(0).min(max)
+}
@@ -0,0 +1,271 @@
+//! Serializes an iterator of `i64` into a sparse representation.
+//!
+//! Instead of a dense array, the output is a struct with two fields:
+//! `len`, the number of elements, and `nonzero`, a map from the index of each nonzero element to
+//! its value, i.e. `{"len": N, "nonzero": {index: value, ...}}`.
+//! This is far smaller than a dense array for mostly-zero data.
+//!
+//! The `deserialize` function reconstructs the dense sequence into any `FromIterator<i64>`
+//! container.
+//! Since `len` is read from the input, it is rejected if it exceeds [`MAX_LEN`],
+//! so that a small input cannot make the deserializer allocate a huge sequence.
+//! For the output to round-trip, `serialize` also fails on more than [`MAX_LEN`] elements.
+//!
+//! *This module requires the "sparse" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_iter::sparse")]
+//!     bar: Vec<i64>,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: vec![0, 0, 5, 0, 7],
+//! };
+//! let value = serde_json::to_value(&foo).unwrap();
+//! assert_eq!(value, serde_json::json!({
+//!     "bar": {"len": 5, "nonzero": {"2": 5, "4": 7}}
+//! }));
+//!
+//! let foo: Foo = serde_json::from_value(value).unwrap();
+//! assert_eq!(foo.bar, vec![0, 0, 5, 0, 7]);
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Error, Serialize, SerializeMap, SerializeStruct, Serializer};
+
+const FIELDS: &[&str] = &["len", "nonzero"];
+
+/// The maximum `len` accepted by [`serialize`] and [`deserialize`].
+pub const MAX_LEN: usize = 1 << 24;
+
+/// The error for a sequence longer than [`MAX_LEN`].
+struct TooLong;
+
+impl fmt::Display for TooLong {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "length exceeds the maximum of {}", MAX_LEN)
+    }
+}
+
+/// Refer to the [module-level documentation](index.html).
+///
+/// Returns an error if the iterator has more than [`MAX_LEN`] elements,
+/// since `deserialize` would not accept the output.
+pub fn serialize<S, T>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = i64> + Clone,
+{
+    let mut len = 0_usize;
+    let mut nonzero = Vec::new();
+    for (index, value) in iter.clone().into_iter().enumerate() {
+        if index >= MAX_LEN {
+            return Err(S::Error::custom(TooLong));
+        }
+        if value != 0 {
+            nonzero.push((index, value));
+        }
+        len = index + 1;
+    }
+
+    let mut st = serializer.serialize_struct("Sparse", 2)?;
+    st.serialize_field("len", &len)?;
+    st.serialize_field("nonzero", &Nonzero(&nonzero))?;
+    st.end()
+}
+
+struct Nonzero<'a>(&'a [(usize, i64)]);
+
+impl<'a> Serialize for Nonzero<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (index, value) in self.0 {
+            map.serialize_entry(index, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Refer to the [module-level documentation](index.html).
+///
+/// Returns an error if `len` exceeds [`MAX_LEN`],
+/// or if a nonzero index is not less than `len`.
+pub fn deserialize<'de, D, C>(deserializer: D) -> Result<C, D::Error>
+where
+    D: Deserializer<'de>,
+    C: FromIterator<i64>,
+{
+    deserializer.deserialize_struct("Sparse", FIELDS, SparseVisitor(PhantomData))
+}
+
+struct SparseVisitor<C>(PhantomData<C>);
+
+impl<'de, C> Visitor<'de> for SparseVisitor<C>
+where
+    C: FromIterator<i64>,
+{
+    type Value = C;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sparse sequence with a length and a map of nonzero elements")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<C, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let len = seq
+            .next_element::<usize>()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let nonzero = seq
+            .next_element::<BTreeMap<usize, i64>>()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        densify(len, &nonzero)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<C, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut len = None;
+        let mut nonzero = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "len" => {
+                    if len.is_some() {
+                        return Err(de::Error::duplicate_field("len"));
+                    }
+                    len = Some(map.next_value::<usize>()?);
+                }
+                "nonzero" => {
+                    if nonzero.is_some() {
+                        return Err(de::Error::duplicate_field("nonzero"));
+                    }
+                    nonzero = Some(map.next_value::<BTreeMap<usize, i64>>()?);
+                }
+                other => return Err(de::Error::unknown_field(other, FIELDS)),
+            }
+        }
+        let len = len.ok_or_else(|| de::Error::missing_field("len"))?;
+        let nonzero = nonzero.ok_or_else(|| de::Error::missing_field("nonzero"))?;
+        densify(len, &nonzero)
+    }
+}
+
+fn densify<C, E>(len: usize, nonzero: &BTreeMap<usize, i64>) -> Result<C, E>
+where
+    C: FromIterator<i64>,
+    E: de::Error,
+{
+    if len > MAX_LEN {
+        return Err(E::custom(TooLong));
+    }
+    if let Some((&index, _)) = nonzero.range(len..).next() {
+        return Err(E::custom(format_args!(
+            "nonzero index {} is out of bounds for length {}",
+            index, len
+        )));
+    }
+    Ok((0..len)
+        .map(|index| nonzero.get(&index).copied().unwrap_or(0))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::{from_value, json, to_value};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Foo {
+        #[serde(with = "super")]
+        bar: Vec<i64>,
+    }
+
+    #[test]
+    fn test_empty() {
+        let value = to_value(Foo { bar: vec![] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"len": 0, "nonzero": {}}
+            })
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let value = to_value(Foo {
+            bar: vec![0, 0, 5, 0, 7],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": {"len": 5, "nonzero": {"2": 5, "4": 7}}
+            })
+        );
+
+        let foo: Foo = from_value(value).expect("Failed to deserialize");
+        assert_eq!(
+            foo,
+            Foo {
+                bar: vec![0, 0, 5, 0, 7]
+            }
+        );
+    }
+
+    #[test]
+    fn test_out_of_bounds() {
+        let result = from_value::<Foo>(json!({
+            "bar": {"len": 2, "nonzero": {"2": 5}}
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_serialize_exceeds_max() {
+        #[derive(Serialize)]
+        struct Endless {
+            #[serde(with = "super")]
+            bar: std::iter::Repeat<i64>,
+        }
+
+        let err = to_value(Endless {
+            bar: std::iter::repeat(0),
+        });
+        let err = err.expect_err("Serialized more than MAX_LEN elements");
+        assert!(err.to_string().contains("exceeds the maximum"));
+    }
+
+    #[test]
+    fn test_len_exceeds_max() {
+        let huge = from_value::<Foo>(json!({
+            "bar": {"len": usize::MAX, "nonzero": {}}
+        }));
+        assert!(huge.is_err());
+
+        let above_max = from_value::<Foo>(json!({
+            "bar": {"len": super::MAX_LEN + 1, "nonzero": {}}
+        }));
+        assert!(above_max.is_err());
+
+        let foo = from_value::<Foo>(json!({
+            "bar": {"len": 3, "nonzero": {"1": 4}}
+        }));
+        let foo = foo.expect("Failed to deserialize");
+        assert_eq!(foo.bar, vec![0, 4, 0]);
+    }
+}
@@ -0,0 +1,92 @@
+//! Serializes an iterator of serializables into a serde sequence of sub-sequences,
+//! splitting at each occurrence of a boundary value.
+//!
+//! This is useful for protocols where a sentinel value separates records.
+//! The boundary values themselves are not emitted.
+//! As with `slice::split`, a leading, trailing or repeated boundary produces an empty segment,
+//! so the output always contains one more segment than there are boundaries.
+//!
+//! Each segment is buffered before it is serialized.
+//!
+//! *This module requires the "split" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! let mut buf = Vec::new();
+//! serde_iter::split::serialize(
+//!     &vec![1, 0, 2, 3, 0, 4],
+//!     &0,
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), "[[1],[2,3],[4]]");
+//! ```
+
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, V>(iter: &T, boundary: &V, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize + PartialEq,
+{
+    let mut seq = serializer.serialize_seq(None)?;
+    let mut segment = Vec::new();
+    for value in iter.clone() {
+        if value == *boundary {
+            seq.serialize_element(&segment)?;
+            segment.clear();
+        } else {
+            segment.push(value);
+        }
+    }
+    seq.serialize_element(&segment)?;
+    seq.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_value};
+
+    type Values = Vec<i32>;
+
+    fn on_zero<S: Serializer>(iter: &Values, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, &0, serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(serialize_with = "on_zero")]
+        bar: Values,
+    }
+
+    #[test]
+    fn test_split() {
+        let value = to_value(Foo {
+            bar: vec![1, 0, 2, 3, 0, 4],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": [[1], [2, 3], [4]]
+            })
+        );
+    }
+
+    #[test]
+    fn test_empty_segments() {
+        let value = to_value(Foo {
+            bar: vec![0, 1, 0, 0],
+        });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": [[], [1], [], []]
+            })
+        );
+    }
+}
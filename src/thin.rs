@@ -0,0 +1,104 @@
+//! Serializes an iterator of serializables into a serde sequence,
+//! keeping progressively fewer elements as the index grows.
+//!
+//! The first `head` elements are all kept.
+//! After that, the stream is divided into bands that double in length,
+//! i.e. `[head, 2 * head)`, `[2 * head, 4 * head)`, `[4 * head, 8 * head)` and so on,
+//! and every 2nd, 4th, 8th, ... element is kept in the respective band.
+//! As a result, each band contributes about `head / 2` elements,
+//! and the output size grows logarithmically with the length of the stream.
+//! This is useful for previewing huge streams.
+//!
+//! The kept elements are emitted in their original order.
+//! Note that the whole iterator is still consumed.
+//!
+//! *This module requires the "thin" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! let mut buf = Vec::new();
+//! serde_iter::thin::serialize(&(0..16), 4, &mut serde_json::Serializer::new(&mut buf)).unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), "[0,1,2,3,4,6,8,12]");
+//! ```
+
+use serde::ser::{Error, Serialize, SerializeSeq, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+///
+/// Returns an error if `head` is zero.
+pub fn serialize<S, T, V>(iter: &T, head: usize, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    if head == 0 {
+        return Err(S::Error::custom("thinning head length must be nonzero"));
+    }
+
+    let mut seq = serializer.serialize_seq(None)?;
+    for (index, value) in iter.clone().into_iter().enumerate() {
+        if is_kept(index, head) {
+            seq.serialize_element(&value)?;
+        }
+    }
+    seq.end()
+}
+
+fn is_kept(index: usize, head: usize) -> bool {
+    if index < head {
+        return true;
+    }
+
+    let band = (index / head).ilog2();
+    let band_start = head << band;
+    match 1_usize.checked_shl(band + 1) {
+        Some(stride) => (index - band_start).is_multiple_of(stride),
+        None => index == band_start,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_value, Value};
+
+    fn head_ten<S: Serializer>(iter: &Range<u32>, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(iter, 10, serializer)
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(serialize_with = "head_ten")]
+        bar: Range<u32>,
+    }
+
+    #[test]
+    fn test_short() {
+        let value = to_value(Foo { bar: 0..5 });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": [0, 1, 2, 3, 4]
+            })
+        );
+    }
+
+    #[test]
+    fn test_bounded() {
+        let value = to_value(Foo { bar: 0..1000 });
+        let value = value.expect("Failed to serialize");
+        let indices: Vec<u64> = match value.get("bar") {
+            Some(Value::Array(array)) => array.iter().filter_map(Value::as_u64).collect(),
+            _ => panic!("Expected an array"),
+        };
+
+        // 10 in the head, 5 in each of the 6 full bands, 3 in the partial band [640, 1000)
+        assert_eq!(indices.len(), 43);
+        assert_eq!(indices.get(..10), Some(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9][..]));
+        assert!(indices.windows(2).all(|pair| pair.first() < pair.get(1)));
+    }
+}
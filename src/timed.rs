@@ -0,0 +1,86 @@
+//! Serializes an iterator of serializables into a serde sequence,
+//! reporting the time taken to serialize each element.
+//!
+//! The observer is called after each element is serialized,
+//! with the index of the element and the time taken to serialize it.
+//! This helps finding expensive elements in mixed sequences.
+//! The time taken to produce the element from the iterator is not included.
+//!
+//! *This module requires the "timed" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! let mut timings = Vec::new();
+//!
+//! let mut buf = Vec::new();
+//! serde_iter::timed::serialize(
+//!     &vec!["foo", "bar"],
+//!     |index, duration| timings.push((index, duration)),
+//!     &mut serde_json::Serializer::new(&mut buf),
+//! )
+//! .unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), r#"["foo","bar"]"#);
+//! assert_eq!(timings.len(), 2);
+//! ```
+
+use std::time::{Duration, Instant};
+
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, V, O>(iter: &T, mut observer: O, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+    O: FnMut(usize, Duration),
+{
+    let iter = iter.clone().into_iter();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    for (index, value) in iter.enumerate() {
+        let start = Instant::now();
+        seq.serialize_element(&value)?;
+        observer(index, start.elapsed());
+    }
+    seq.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::ops::Range;
+
+    use serde::{Serialize, Serializer};
+    use serde_json::{json, to_value};
+
+    thread_local! {
+        static INDICES: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record<S: Serializer>(iter: &Range<i32>, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize(
+            iter,
+            |index, _| INDICES.with(|indices| indices.borrow_mut().push(index)),
+            serializer,
+        )
+    }
+
+    #[derive(Serialize)]
+    struct Foo {
+        #[serde(serialize_with = "record")]
+        bar: Range<i32>,
+    }
+
+    #[test]
+    fn test_observer() {
+        let value = to_value(Foo { bar: 5..8 });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": [5, 6, 7]
+            })
+        );
+        INDICES.with(|indices| assert_eq!(*indices.borrow(), vec![0, 1, 2]));
+    }
+}
@@ -0,0 +1,120 @@
+//! Serializes an iterator of serializables into a serde tuple of a fixed length.
+//!
+//! Some formats encode tuples differently from sequences,
+//! e.g. bincode omits the length prefix of tuples,
+//! so this is suitable for iterators that always produce the same number of elements,
+//! such as the coordinates of a point.
+//! The expected length is passed as the const generic parameter `N`,
+//! and serialization fails if the iterator produces more or fewer than `N` elements.
+//! The length is checked on a clone of the iterator before anything is written,
+//! so a failed serialization leaves no partial tuple in the output.
+//!
+//! *This module requires the "tuple" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! #[derive(serde::Serialize)]
+//! struct Foo {
+//!     #[serde(serialize_with = "serde_iter::tuple::serialize::<_, _, _, 3>")]
+//!     bar: Vec<u8>,
+//! }
+//!
+//! let foo = Foo {
+//!     bar: vec![1, 2, 3],
+//! };
+//! assert_eq!(bincode::serialize(&foo).unwrap(), [1, 2, 3]);
+//! assert_eq!(serde_json::to_value(&foo).unwrap(), serde_json::json!({
+//!     "bar": [1, 2, 3]
+//! }));
+//! ```
+
+use serde::ser::{Error, Serialize, SerializeTuple, Serializer};
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, V, const N: usize>(iter: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    V: Serialize,
+{
+    let count = iter.clone().into_iter().take(N.saturating_add(1)).count();
+    if count > N {
+        return Err(S::Error::custom(format_args!(
+            "expected exactly {} elements, got more",
+            N
+        )));
+    }
+    if count < N {
+        return Err(S::Error::custom(format_args!(
+            "expected exactly {} elements, got {}",
+            N, count
+        )));
+    }
+
+    let mut tuple = serializer.serialize_tuple(N)?;
+    for value in iter.clone() {
+        tuple.serialize_element(&value)?;
+    }
+    tuple.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde_json::{json, to_value};
+
+    #[derive(Serialize)]
+    struct Foo<T>
+    where
+        T: IntoIterator<Item = u8> + Clone,
+    {
+        #[serde(serialize_with = "super::serialize::<_, _, _, 3>")]
+        bar: T,
+    }
+
+    #[test]
+    fn test_exact() {
+        let value = to_value(Foo { bar: vec![1, 2, 3] });
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!({
+                "bar": [1, 2, 3]
+            })
+        );
+
+        let encoded = bincode::serialize(&Foo { bar: 1..4 });
+        let encoded = encoded.expect("Failed to serialize");
+        assert_eq!(encoded, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_too_few() {
+        let err = to_value(Foo { bar: vec![1, 2] });
+        let err = err.expect_err("Serialized a short tuple");
+        assert_eq!(err.to_string(), "expected exactly 3 elements, got 2");
+    }
+
+    #[test]
+    fn test_too_many() {
+        let err = to_value(Foo { bar: 0.. });
+        let err = err.expect_err("Serialized a long tuple");
+        assert_eq!(err.to_string(), "expected exactly 3 elements, got more");
+    }
+
+    #[test]
+    fn test_no_partial_output() {
+        let mut buf = Vec::new();
+        let result = super::serialize::<_, _, _, 3>(
+            &vec![1_u8, 2],
+            &mut serde_json::Serializer::new(&mut buf),
+        );
+        assert!(result.is_err());
+        assert!(buf.is_empty());
+
+        let long =
+            super::serialize::<_, _, _, 3>(&(0_u8..), &mut serde_json::Serializer::new(&mut buf));
+        assert!(long.is_err());
+        assert!(buf.is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+//! Serializes an iterator into a serde sequence,
+//! projecting each element in a compact or a detailed form depending on a verbosity level.
+//!
+//! Each element is passed to the compact projection function at [`Level::Compact`],
+//! or to the detailed projection function at [`Level::Detailed`],
+//! and the result is serialized in place of the element.
+//! This is useful for adjustable log detail, e.g. emitting only IDs by default
+//! and full objects when debugging.
+//!
+//! The [`Verbosity`](struct.Verbosity.html) wrapper holds the iterator together with the level
+//! and both projections, so it can be serialized without a `serialize_with` function.
+//!
+//! [`Level::Compact`]: enum.Level.html#variant.Compact
+//! [`Level::Detailed`]: enum.Level.html#variant.Detailed
+//!
+//! *This module requires the "verbosity" feature to be enabled.*
+//!
+//! # Example
+//! ```
+//! use serde_iter::verbosity::{Level, Verbosity};
+//!
+//! let users = vec![(1, "alice"), (2, "bob")];
+//! let compact = |&(id, _): &(u32, &'static str)| id;
+//! let detailed = |&user: &(u32, &'static str)| user;
+//!
+//! let ids = Verbosity::new(users.clone(), Level::Compact, compact, detailed);
+//! assert_eq!(serde_json::to_value(&ids).unwrap(), serde_json::json!([1, 2]));
+//!
+//! let full = Verbosity::new(users, Level::Detailed, compact, detailed);
+//! assert_eq!(
+//!     serde_json::to_value(&full).unwrap(),
+//!     serde_json::json!([[1, "alice"], [2, "bob"]]),
+//! );
+//! ```
+
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+/// The verbosity level selecting the projection of each element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    /// Serializes each element with the compact projection.
+    Compact,
+    /// Serializes each element with the detailed projection.
+    Detailed,
+}
+
+/// Refer to the [module-level documentation](index.html).
+pub fn serialize<S, T, V, F, G, C, D>(
+    iter: &T,
+    level: Level,
+    compact_fn: F,
+    detailed_fn: G,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: IntoIterator<Item = V> + Clone,
+    F: Fn(&V) -> C,
+    G: Fn(&V) -> D,
+    C: Serialize,
+    D: Serialize,
+{
+    let iter = iter.clone().into_iter();
+    let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
+    for value in iter {
+        match level {
+            Level::Compact => seq.serialize_element(&compact_fn(&value))?,
+            Level::Detailed => seq.serialize_element(&detailed_fn(&value))?,
+        }
+    }
+    seq.end()
+}
+
+/// Wraps an iterator so that it serializes in the form selected by a verbosity level.
+///
+/// Refer to the [module-level documentation](index.html).
+#[derive(Debug, Clone)]
+pub struct Verbosity<I, F, G> {
+    iter: I,
+    level: Level,
+    compact_fn: F,
+    detailed_fn: G,
+}
+
+impl<I, F, G> Verbosity<I, F, G> {
+    /// Creates a sequence of the elements of `iter`,
+    /// projected with `compact_fn` or `detailed_fn` depending on `level`.
+    pub fn new(iter: I, level: Level, compact_fn: F, detailed_fn: G) -> Self {
+        Self {
+            iter,
+            level,
+            compact_fn,
+            detailed_fn,
+        }
+    }
+}
+
+impl<I, F, G, V, C, D> Serialize for Verbosity<I, F, G>
+where
+    I: IntoIterator<Item = V> + Clone,
+    F: Fn(&V) -> C,
+    G: Fn(&V) -> D,
+    C: Serialize,
+    D: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(
+            &self.iter,
+            self.level,
+            &self.compact_fn,
+            &self.detailed_fn,
+            serializer,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde_json::{json, to_value};
+
+    use super::{Level, Verbosity};
+
+    #[derive(Serialize, Clone)]
+    struct User {
+        id: u32,
+        name: &'static str,
+    }
+
+    const USERS: [User; 2] = [
+        User {
+            id: 1,
+            name: "alice",
+        },
+        User { id: 2, name: "bob" },
+    ];
+
+    fn verbosity(level: Level) -> impl Serialize {
+        Verbosity::new(
+            USERS.iter(),
+            level,
+            |user: &&User| user.id,
+            |user: &&User| (*user).clone(),
+        )
+    }
+
+    #[test]
+    fn test_compact() {
+        let value = to_value(verbosity(Level::Compact));
+        let value = value.expect("Failed to serialize");
+        assert_eq!(value, json!([1, 2]));
+    }
+
+    #[test]
+    fn test_detailed() {
+        let value = to_value(verbosity(Level::Detailed));
+        let value = value.expect("Failed to serialize");
+        assert_eq!(
+            value,
+            json!([
+                {"id": 1, "name": "alice"},
+                {"id": 2, "name": "bob"}
+            ])
+        );
+    }
+}